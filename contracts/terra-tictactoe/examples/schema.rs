@@ -4,8 +4,11 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use cw_controllers::AdminResponse;
 use terra_tictactoe::msg::{
-    ExecuteMsg, GameResponse, GamesResponse, InstantiateMsg, LeaderboardResponse, LockedResponse,
-    QueryMsg,
+    BetMarketResponse, BetResponse, ExecuteMsg, GameResponse, GamesResponse, HouseResponse,
+    HouseSharesResponse, InstantiateMsg, JackpotDrawResponse, JackpotResponse, LeaderboardResponse,
+    LeaderboardSeasonResponse, LeagueResponse, LockedResponse, MoveKeyResponse, MovesResponse,
+    PendingDistributionResponse, QueryMsg, ReferralStatsResponse, SeriesResponse,
+    SessionKeyResponse, StandingsResponse, TournamentResponse, TournamentsResponse,
 };
 
 fn main() {
@@ -22,4 +25,21 @@ fn main() {
     export_schema(&schema_for!(GamesResponse), &out_dir);
     export_schema(&schema_for!(AdminResponse), &out_dir);
     export_schema(&schema_for!(LeaderboardResponse), &out_dir);
+    export_schema(&schema_for!(MovesResponse), &out_dir);
+    export_schema(&schema_for!(ReferralStatsResponse), &out_dir);
+    export_schema(&schema_for!(PendingDistributionResponse), &out_dir);
+    export_schema(&schema_for!(JackpotResponse), &out_dir);
+    export_schema(&schema_for!(JackpotDrawResponse), &out_dir);
+    export_schema(&schema_for!(HouseResponse), &out_dir);
+    export_schema(&schema_for!(HouseSharesResponse), &out_dir);
+    export_schema(&schema_for!(SessionKeyResponse), &out_dir);
+    export_schema(&schema_for!(MoveKeyResponse), &out_dir);
+    export_schema(&schema_for!(SeriesResponse), &out_dir);
+    export_schema(&schema_for!(TournamentResponse), &out_dir);
+    export_schema(&schema_for!(TournamentsResponse), &out_dir);
+    export_schema(&schema_for!(BetMarketResponse), &out_dir);
+    export_schema(&schema_for!(BetResponse), &out_dir);
+    export_schema(&schema_for!(LeaderboardSeasonResponse), &out_dir);
+    export_schema(&schema_for!(LeagueResponse), &out_dir);
+    export_schema(&schema_for!(StandingsResponse), &out_dir);
 }
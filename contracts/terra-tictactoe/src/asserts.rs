@@ -1,6 +1,10 @@
-use cosmwasm_std::{Coin, Deps};
+use cosmwasm_std::{Addr, Coin, Deps};
 
-use crate::state::CONFIG;
+use crate::fees::TOTAL_WEIGHT;
+use crate::game::MAX_SEATS;
+use crate::leaderboard::MAX_REWARDED_PLACES;
+use crate::state::{BoardRules, Config, FeeCaps, FeeShare, Game, GameState, RuleSet, CONFIG};
+use crate::tournament::{rounds, MAX_BRACKET_SIZE};
 use crate::ContractError;
 
 pub fn assert_is_locked(deps: Deps) -> Result<(), ContractError> {
@@ -23,3 +27,142 @@ pub fn assert_host_bet(deps: Deps, bet: &Coin) -> Result<(), ContractError> {
 
     Ok(())
 }
+
+/// Only the players of a finished game that was played for a stake can ask for a rematch.
+pub fn assert_rematch(game: &Game, player: &Addr) -> Result<(), ContractError> {
+    match game.state {
+        GameState::Completed | GameState::PriceWithdrawn | GameState::Drawn => {}
+        _ => {
+            return Err(ContractError::NotAllowedInCurrentState {
+                state: game.state.clone(),
+            })
+        }
+    }
+    if game.tournament_id.is_some()
+        || game.season_id.is_some()
+        || game.bet.amount.is_zero()
+        || game.seats != 2
+        || game.vs_house
+    {
+        return Err(ContractError::RematchNotAllowed {});
+    }
+    if !game.players.contains(player) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+/// Games seat two to `MAX_SEATS` players, misère and ultimate tic-tac-toe only two.
+pub fn assert_seats(seats: u8, rule_set: RuleSet) -> Result<(), ContractError> {
+    let max_seats = match rule_set {
+        RuleSet::Misere | RuleSet::Ultimate => 2,
+        _ => MAX_SEATS,
+    };
+    if seats < 2 || seats > max_seats {
+        return Err(ContractError::InvalidSeats {});
+    }
+
+    Ok(())
+}
+
+pub fn assert_best_of(best_of: u8) -> Result<(), ContractError> {
+    if ![3, 5, 7].contains(&best_of) {
+        return Err(ContractError::InvalidBestOf {});
+    }
+
+    Ok(())
+}
+
+pub fn assert_board_rules(rules: &BoardRules) -> Result<(), ContractError> {
+    // The disabled cells of a board are drawn from the 32 bytes of a Terrand round
+    if rules.threshold < 3 || rules.threshold > 15 || rules.dimension < rules.threshold {
+        return Err(ContractError::InvalidDimension {});
+    }
+
+    Ok(())
+}
+
+pub fn assert_tournament(
+    bracket_size: u32,
+    prize_distribution: &[u16],
+) -> Result<(), ContractError> {
+    if !bracket_size.is_power_of_two() || !(2..=MAX_BRACKET_SIZE).contains(&bracket_size) {
+        return Err(ContractError::InvalidBracketSize {});
+    }
+    // One share for the champion and one for the losers of each round
+    let places = rounds(bracket_size) as usize + 1;
+    let total: u32 = prize_distribution.iter().map(|bps| u32::from(*bps)).sum();
+    if prize_distribution.is_empty()
+        || prize_distribution.len() > places
+        || total != u32::from(TOTAL_WEIGHT)
+    {
+        return Err(ContractError::InvalidPrizeDistribution {});
+    }
+
+    Ok(())
+}
+
+pub fn assert_reward_distribution(
+    reward_pool: &[Coin],
+    reward_distribution: &[u16],
+) -> Result<(), ContractError> {
+    let total: u32 = reward_distribution.iter().map(|bps| u32::from(*bps)).sum();
+    if reward_distribution.len() > MAX_REWARDED_PLACES
+        || (!reward_pool.is_empty() && total != u32::from(TOTAL_WEIGHT))
+        || total > u32::from(TOTAL_WEIGHT)
+    {
+        return Err(ContractError::InvalidRewardDistribution {});
+    }
+
+    Ok(())
+}
+
+pub fn assert_fee_config(config: &Config) -> Result<(), ContractError> {
+    if config.max_fee_bps > TOTAL_WEIGHT
+        || config.jackpot_bps > TOTAL_WEIGHT
+        || config.house_exposure_bps > TOTAL_WEIGHT
+        || config.house_max_stake_bps > TOTAL_WEIGHT
+        || config.house_edge_bps > TOTAL_WEIGHT
+        || config.fee_bps > config.max_fee_bps
+        || config
+            .fee_tiers
+            .iter()
+            .any(|tier| tier.fee_bps > config.max_fee_bps)
+    {
+        return Err(ContractError::InvalidFeeBps {});
+    }
+    if config.referral_percentage > 100 {
+        return Err(ContractError::InvalidReferralPercentage {});
+    }
+
+    Ok(())
+}
+
+pub fn assert_fee_caps(caps: &FeeCaps) -> Result<(), ContractError> {
+    if let (Some(min_fee), Some(max_fee)) = (caps.min_fee, caps.max_fee) {
+        if min_fee > max_fee {
+            return Err(ContractError::InvalidFeeCaps {});
+        }
+    }
+
+    Ok(())
+}
+
+/// The fee charged on `stake` has to leave something in the pot.
+pub fn assert_stake_covers_fee(stake: &Coin, fee: &Coin) -> Result<(), ContractError> {
+    if fee.amount >= stake.amount {
+        return Err(ContractError::StakeBelowFee {});
+    }
+
+    Ok(())
+}
+
+pub fn assert_fee_distribution(shares: &[FeeShare]) -> Result<(), ContractError> {
+    let total: u32 = shares.iter().map(|share| u32::from(share.weight)).sum();
+    if shares.iter().any(|share| share.weight == 0) || total != u32::from(TOTAL_WEIGHT) {
+        return Err(ContractError::InvalidFeeDistribution {});
+    }
+
+    Ok(())
+}
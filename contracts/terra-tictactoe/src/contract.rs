@@ -1,29 +1,71 @@
+use std::collections::BTreeMap;
+
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Order, Response,
-    StdResult, SubMsg,
+    to_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, Event, MessageInfo, Order,
+    Response, StdError, StdResult, Storage, SubMsg, Timestamp, Uint128,
 };
 use cw0::maybe_addr;
 use cw2::set_contract_version;
-use cw_storage_plus::U64Key;
+use cw_storage_plus::{Bound, U32Key, U64Key};
 
-use crate::asserts::{assert_host_bet, assert_is_locked};
+use crate::asserts::{
+    assert_best_of, assert_board_rules, assert_fee_caps, assert_fee_config,
+    assert_fee_distribution, assert_host_bet, assert_is_locked, assert_rematch,
+    assert_reward_distribution, assert_seats, assert_stake_covers_fee, assert_tournament,
+};
 use crate::error::ContractError;
-use crate::game::{get_mark_for_cell, get_next_player, is_game_completed};
+use crate::events;
+use crate::fees::{accrue_fee, fee_for, split_fees};
+use crate::game::{
+    active_players, get_mark_for_cell, get_next_player, get_opponent_of, house_move, is_board_full,
+    new_boards, new_grid, seat_holder, target_cell, variant_rules, winner_after_move,
+    MAX_TEAMMATES,
+};
+use crate::house::{
+    deposit, house_edge, max_exposure, reserve_stake, settle_stake, share_value, withdraw,
+};
+use crate::jackpot::{fund_jackpot, is_jackpot_eligible, lucky_draw, new_draw, take_jackpot};
+use crate::leaderboard::{rank_entries, season_rewards};
+use crate::league::{apply_outcome, round_robin, sort_standings, Outcome, MAX_LEAGUE_PLAYERS};
 use crate::msg::{
-    ExecuteMsg, GameResponse, GamesResponse, InstantiateMsg, LeaderBoardEntry, LeaderboardResponse,
-    LockedResponse, QueryMsg,
+    BetMarketResponse, BetResponse, ExecuteMsg, FeeShareMsg, GameResponse, GamesResponse,
+    HouseResponse, HouseSharesResponse, InstantiateMsg, JackpotDrawResponse, JackpotResponse,
+    LeaderBoardEntry, LeaderboardResponse, LeaderboardSeasonResponse, LeagueResponse,
+    LockedResponse, MoveKeyResponse, MovesResponse, PendingDistributionResponse, PendingShare,
+    QueryMsg, ReferralStatsResponse, SeriesResponse, SessionKeyResponse, SignedMove,
+    StandingsResponse, TournamentResponse, TournamentsResponse,
 };
+use crate::referral::{credit_referral, register_referral};
+use crate::series::{next_pairing, score_game};
+use crate::session::{is_session_valid, new_session};
+use crate::settlement::{settle, share_with_teams, split_pot, Settlement};
+use crate::signed::{is_equivocation, is_signed_by};
 use crate::state::{
-    games, next_id, Config, Game, GameState, ADMIN, CONFIG, GAMES_COUNT, LEADERBOARD,
+    games, next_id, next_series_id, next_tournament_id, BetMarket, BetOutcome, BetPosition,
+    BoardRules, Config, FeeCaps, FeeShare, FeeTier, Game, GameState, LeaderboardSeason, League,
+    LeaguePoints, LeagueSeason, Move, Offer, OfferKind, RuleSet, SeasonState, Series, SeriesState,
+    Standing, Tournament, TournamentState, ADMIN, BETS, BET_MARKETS, CONFIG, CURRENT_SEASON,
+    FEE_CAPS, FEE_DISTRIBUTION, FEE_POOL, GAMES_COUNT, GAME_MEMBERS, HOUSE, HOUSE_SHARES, JACKPOT,
+    JACKPOT_DRAWS, LEADERBOARD, LEADERBOARD_SEASONS, LEAGUE, LEAGUE_SEASONS, MOVES, MOVE_KEYS,
+    REFERRAL_STATS, REFERRERS, SEASON_LEADERBOARD, SERIES, SESSION_KEYS, STANDINGS, TOURNAMENTS,
+};
+use crate::tournament::{close_round, pairings, prize_payouts, record_winner, seed_bracket};
+use crate::ultimate::{play as play_ultimate, BoardResult};
+use crate::utils::{add_coin, get_latest_round, get_randomness, get_round_randomness};
+use crate::wagering::{
+    add_stake, is_betting_open, payout, record_claim, resolve, total_stake, Resolution,
 };
-use crate::utils::{generate_random_u8, get_randomness};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:terra_tictactoe";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// pagination defaults
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -32,16 +74,26 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     let state = Config {
-        fee_percentage: 2,
+        fee_bps: 200,
+        max_fee_bps: 1_000,
+        fee_tiers: vec![],
         locked: false,
         dimension: 6,
         threshold: 4,
         min_bet: msg.min_bet,
         terrand_address: deps.api.addr_validate(&msg.terrand_address)?,
+        referral_percentage: 0,
+        jackpot_bps: 0,
+        jackpot_odds: 0,
+        bets_close_after: 4,
+        house_exposure_bps: 0,
+        house_max_stake_bps: 0,
+        house_edge_bps: 0,
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     CONFIG.save(deps.storage, &state)?;
     GAMES_COUNT.save(deps.storage, &0)?;
+    FEE_POOL.save(deps.storage, &vec![])?;
     ADMIN.set(deps, Some(info.sender.clone()))?;
 
     Ok(Response::new()
@@ -61,16 +113,136 @@ pub fn execute(
         ExecuteMsg::UpdateConfig {
             threshold,
             dimension,
-            fee_percentage,
-        } => try_update_config(deps, info, threshold, dimension, fee_percentage),
-        ExecuteMsg::CreateGame {} => try_create_game(_env, deps, info),
-        ExecuteMsg::CancelGame { game_id } => try_cancel_game(deps, info, game_id),
-        ExecuteMsg::JoinGame { game_id } => try_join_game(_env, deps, info, game_id),
+            fee_bps,
+            referral_percentage,
+        } => try_update_config(
+            deps,
+            info,
+            threshold,
+            dimension,
+            fee_bps,
+            referral_percentage,
+        ),
+        ExecuteMsg::UpdateFeeLimits {
+            max_fee_bps,
+            fee_tiers,
+        } => try_update_fee_limits(deps, info, max_fee_bps, fee_tiers),
+        ExecuteMsg::UpdateJackpot {
+            jackpot_bps,
+            jackpot_odds,
+        } => try_update_jackpot(deps, info, jackpot_bps, jackpot_odds),
+        ExecuteMsg::UpdateFeeCaps {
+            denom,
+            min_fee,
+            max_fee,
+        } => try_update_fee_caps(deps, info, denom, min_fee, max_fee),
+        ExecuteMsg::UpdateHouse {
+            exposure_bps,
+            max_stake_bps,
+            edge_bps,
+        } => try_update_house(deps, info, exposure_bps, max_stake_bps, edge_bps),
+        ExecuteMsg::DepositHouse {} => try_deposit_house(deps, info),
+        ExecuteMsg::WithdrawHouse { denom, shares } => {
+            try_withdraw_house(deps, info, denom, shares)
+        }
+        ExecuteMsg::CreateGame {
+            auto_payout,
+            referrer,
+            rule_set,
+            seats,
+            vs_house,
+        } => try_create_game(
+            _env,
+            deps,
+            info,
+            auto_payout,
+            referrer,
+            rule_set,
+            seats,
+            vs_house,
+        ),
+        ExecuteMsg::CancelGame { game_id } => try_cancel_game(_env, deps, info, game_id),
+        ExecuteMsg::JoinGame { game_id, referrer } => {
+            try_join_game(_env, deps, info, game_id, referrer)
+        }
+        ExecuteMsg::AddTeammate { game_id, teammate } => {
+            try_add_teammate(_env, deps, info, game_id, teammate)
+        }
         ExecuteMsg::MakeMove { game_id, x, y } => try_make_move(_env, deps, info, game_id, x, y),
-        ExecuteMsg::WithdrawPrice { game_id } => try_withdraw_price(_env, deps, game_id),
-        ExecuteMsg::WithdrawFunds { funds_to_withdraw } => {
-            try_withdraw_funds(deps, info, funds_to_withdraw)
+        ExecuteMsg::AuthorizeSessionKey {
+            key,
+            game_id,
+            duration,
+        } => try_authorize_session_key(_env, deps, info, key, game_id, duration),
+        ExecuteMsg::RevokeSessionKey { key } => try_revoke_session_key(deps, info, key),
+        ExecuteMsg::RegisterMoveKey { pubkey } => try_register_move_key(deps, info, pubkey),
+        ExecuteMsg::SubmitSignedMoves { game_id, moves } => {
+            try_submit_signed_moves(_env, deps, info, game_id, moves)
+        }
+        ExecuteMsg::ReportEquivocation {
+            game_id,
+            player,
+            first,
+            second,
+        } => try_report_equivocation(_env, deps, game_id, player, first, second),
+        ExecuteMsg::Resign { game_id } => try_resign(_env, deps, info, game_id),
+        ExecuteMsg::OfferDraw { game_id } => try_make_offer(deps, info, game_id, OfferKind::Draw),
+        ExecuteMsg::AcceptDraw { game_id } => try_accept_draw(_env, deps, info, game_id),
+        ExecuteMsg::DeclineDraw { game_id } => try_decline_draw(deps, info, game_id),
+        ExecuteMsg::RequestTakeback { game_id } => {
+            try_make_offer(deps, info, game_id, OfferKind::Takeback)
+        }
+        ExecuteMsg::AcceptTakeback { game_id } => try_accept_takeback(_env, deps, info, game_id),
+        ExecuteMsg::CreateSeries { best_of } => try_create_series(deps, info, best_of),
+        ExecuteMsg::JoinSeries { series_id } => try_join_series(_env, deps, info, series_id),
+        ExecuteMsg::CancelSeries { series_id } => try_cancel_series(deps, info, series_id),
+        ExecuteMsg::OfferRematch { game_id } => try_offer_rematch(deps, info, game_id),
+        ExecuteMsg::AcceptRematch { game_id } => try_accept_rematch(_env, deps, info, game_id),
+        ExecuteMsg::DeclineRematch { game_id } => try_decline_rematch(deps, info, game_id),
+        ExecuteMsg::WithdrawPrice { game_id } => try_withdraw_price(_env, deps, info, game_id),
+        ExecuteMsg::ClaimAll { limit } => try_claim_all(_env, deps, info, limit),
+        ExecuteMsg::ClaimReferralRewards {} => try_claim_referral_rewards(deps, info),
+        ExecuteMsg::UpdateFeeDistribution { shares } => {
+            try_update_fee_distribution(deps, info, shares)
+        }
+        ExecuteMsg::DistributeFees {} => try_distribute_fees(deps),
+        ExecuteMsg::DrawJackpot { game_id } => try_draw_jackpot(deps, game_id),
+        ExecuteMsg::CreateTournament {
+            entry_fee,
+            bracket_size,
+            rules,
+            prize_distribution,
+        } => try_create_tournament(
+            deps,
+            info,
+            entry_fee,
+            bracket_size,
+            rules,
+            prize_distribution,
+        ),
+        ExecuteMsg::RegisterForTournament { tournament_id } => {
+            try_register_for_tournament(_env, deps, info, tournament_id)
+        }
+        ExecuteMsg::CancelTournament { tournament_id } => {
+            try_cancel_tournament(deps, info, tournament_id)
+        }
+        ExecuteMsg::UpdateBetting { bets_close_after } => {
+            try_update_betting(deps, info, bets_close_after)
         }
+        ExecuteMsg::PlaceBet { game_id, outcome } => {
+            try_place_bet(_env, deps, info, game_id, outcome)
+        }
+        ExecuteMsg::ClaimBet { game_id } => try_claim_bet(deps, info, game_id),
+        ExecuteMsg::StartSeason {
+            reward_distribution,
+        } => try_start_season(_env, deps, info, reward_distribution),
+        ExecuteMsg::ConfigureLeague {
+            season_duration,
+            rules,
+            points,
+        } => try_configure_league(_env, deps, info, season_duration, rules, points),
+        ExecuteMsg::JoinLeagueSeason {} => try_join_league_season(_env, deps, info),
+        ExecuteMsg::AdvanceLeague {} => try_advance_league(_env, deps),
     }
 }
 
@@ -88,101 +260,311 @@ pub fn try_update_config(
     info: MessageInfo,
     threshold: u8,
     dimension: u8,
-    fee_percentage: u8,
+    fee_bps: u16,
+    referral_percentage: u8,
 ) -> Result<Response, ContractError> {
     ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
-    CONFIG.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        state.fee_percentage = fee_percentage;
+    let config = CONFIG.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.fee_bps = fee_bps;
+        state.referral_percentage = referral_percentage;
         state.threshold = threshold;
         state.dimension = dimension;
+        assert_board_rules(&BoardRules {
+            dimension,
+            threshold,
+        })?;
+        assert_fee_config(&state)?;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_event(events::config_updated(&config))
+        .add_attribute("method", "try_update_config"))
+}
+
+pub fn try_update_fee_limits(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_fee_bps: u16,
+    fee_tiers: Vec<FeeTier>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let config = CONFIG.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.max_fee_bps = max_fee_bps;
+        state.fee_tiers = fee_tiers;
+        assert_fee_config(&state)?;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_event(events::config_updated(&config))
+        .add_attribute("method", "try_update_fee_limits"))
+}
+
+pub fn try_update_jackpot(
+    deps: DepsMut,
+    info: MessageInfo,
+    jackpot_bps: u16,
+    jackpot_odds: u16,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let config = CONFIG.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.jackpot_bps = jackpot_bps;
+        state.jackpot_odds = jackpot_odds;
+        assert_fee_config(&state)?;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_event(events::config_updated(&config))
+        .add_attribute("method", "try_update_jackpot"))
+}
+
+pub fn try_update_betting(
+    deps: DepsMut,
+    info: MessageInfo,
+    bets_close_after: u32,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let config = CONFIG.update(deps.storage, |mut state| -> StdResult<_> {
+        state.bets_close_after = bets_close_after;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_event(events::config_updated(&config))
+        .add_attribute("method", "try_update_betting"))
+}
+
+pub fn try_update_fee_caps(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    min_fee: Option<Uint128>,
+    max_fee: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let caps = FeeCaps { min_fee, max_fee };
+    assert_fee_caps(&caps)?;
+    FEE_CAPS.save(deps.storage, &denom, &caps)?;
+
+    Ok(Response::new()
+        .add_event(events::fee_caps_updated(&denom, &caps))
+        .add_attribute("method", "try_update_fee_caps"))
+}
+
+pub fn try_update_house(
+    deps: DepsMut,
+    info: MessageInfo,
+    exposure_bps: u16,
+    max_stake_bps: u16,
+    edge_bps: u16,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let config = CONFIG.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.house_exposure_bps = exposure_bps;
+        state.house_max_stake_bps = max_stake_bps;
+        state.house_edge_bps = edge_bps;
+        assert_fee_config(&state)?;
         Ok(state)
     })?;
 
-    Ok(Response::new().add_attribute("method", "try_update_config"))
+    Ok(Response::new()
+        .add_event(events::config_updated(&config))
+        .add_attribute("method", "try_update_house"))
+}
+
+/// Adds the funds sent along to the bankroll the house matches the stakes of its games with,
+/// the sender gets shares of the pool for them.
+pub fn try_deposit_house(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut response = Response::new();
+    for amount in &info.funds {
+        let mut house = HOUSE
+            .may_load(deps.storage, &amount.denom)?
+            .unwrap_or_default();
+        let shares = deposit(&mut house, amount.amount);
+        HOUSE.save(deps.storage, &amount.denom, &house)?;
+        HOUSE_SHARES.update(
+            deps.storage,
+            (&amount.denom, info.sender.clone()),
+            |held| -> StdResult<_> { Ok(held.unwrap_or_default() + shares) },
+        )?;
+        response = response.add_event(events::house_deposited(&info.sender, amount, shares));
+    }
+
+    Ok(response.add_attribute("method", "try_deposit_house"))
+}
+
+/// Burns `shares` of the pool in `denom` held by the sender and pays out their value, as far
+/// as the bankroll left keeps covering the running house games.
+pub fn try_withdraw_house(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    shares: Uint128,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let held = HOUSE_SHARES
+        .may_load(deps.storage, (&denom, info.sender.clone()))?
+        .unwrap_or_default();
+    if shares.is_zero() || shares > held {
+        return Err(ContractError::InsufficientReserves {});
+    }
+    let mut house = HOUSE.load(deps.storage, &denom)?;
+    let amount = Coin {
+        denom: denom.clone(),
+        amount: withdraw(&mut house, shares, config.house_exposure_bps)?,
+    };
+    HOUSE.save(deps.storage, &denom, &house)?;
+    HOUSE_SHARES.save(
+        deps.storage,
+        (&denom, info.sender.clone()),
+        &(held - shares),
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::new(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![amount.clone()],
+        }))
+        .add_event(events::house_withdrawn(&info.sender, &amount, shares))
+        .add_attribute("method", "try_withdraw_house"))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn try_create_game(
     env: Env,
     deps: DepsMut,
     info: MessageInfo,
+    auto_payout: bool,
+    referrer: Option<String>,
+    rule_set: RuleSet,
+    seats: Option<u8>,
+    vs_house: bool,
 ) -> Result<Response, ContractError> {
     assert_is_locked(deps.as_ref())?;
-    assert_host_bet(deps.as_ref(), &info.funds[0])?;
+    let stake = info
+        .funds
+        .first()
+        .ok_or(ContractError::BetDenomInvalid {})?;
+    assert_host_bet(deps.as_ref(), stake)?;
+    let seats = seats.unwrap_or(2);
+    assert_seats(seats, rule_set)?;
+    // The house only plays one on one on the grid
+    if vs_house && (seats != 2 || rule_set == RuleSet::Ultimate) {
+        return Err(ContractError::HouseGameNotSupported {});
+    }
 
     let config = CONFIG.load(deps.storage)?;
     let randomness = get_randomness(deps.as_ref().querier, config.terrand_address.to_string())?;
 
-    let mut grid: Vec<Vec<i8>> = Vec::new();
-    let mut disabled_cells: Vec<(u8, u8)> = Vec::new();
-    let mut round: usize = 0;
-
-    for _ in 0..config.threshold {
-        let x = generate_random_u8(&randomness, round, config.dimension);
-        round += 1;
-        let y = generate_random_u8(&randomness, round, config.dimension);
-        round += 1;
-        disabled_cells.push((x, y));
-    }
-
-    for i in 0..config.dimension {
-        let mut row: Vec<i8> = Vec::new();
-        for j in 0..config.dimension {
-            if disabled_cells.contains(&(i, j)) {
-                row.push(-1)
-            } else {
-                row.push(0);
-            }
-        }
-        grid.push(row);
-    }
+    let rules = variant_rules(
+        rule_set,
+        BoardRules {
+            dimension: config.dimension,
+            threshold: config.threshold,
+        },
+    );
+    let (grid, ultimate) = new_boards(rule_set, &randomness, &rules);
 
-    let amount = info.funds[0]
-        .amount
-        .multiply_ratio(u128::from(100 - config.fee_percentage), 100u128);
+    let mut fee = fee_for(deps.storage, &config, stake)?;
+    assert_stake_covers_fee(stake, &fee)?;
+    let amount = stake.amount - fee.amount;
+
+    // The house matches the stake out of its bankroll and keeps its edge out of the fee
+    if vs_house {
+        let denom = &config.min_bet.denom;
+        let mut house = HOUSE.may_load(deps.storage, denom)?.unwrap_or_default();
+        reserve_stake(&mut house, amount, &config)?;
+        let edge = house_edge(&config, fee.amount);
+        house.bankroll += edge;
+        fee.amount -= edge;
+        HOUSE.save(deps.storage, denom, &house)?;
+    }
 
     let id = next_id(deps.storage)?;
-    let game = Game {
+    let mut game = Game {
         game_id: id,
         bet: Coin {
-            denom: config.min_bet.denom,
+            denom: config.min_bet.denom.clone(),
             amount,
         },
         host: info.sender.clone(),
         state: GameState::New,
         next_player: info.sender.clone(),
-        grid: grid,
+        grid,
         opponent: Addr::unchecked(""),
         updated_at: env.block.time,
         winner: Addr::unchecked(""),
+        move_count: 0,
+        pending_offer: None,
+        auto_payout,
+        seed: randomness,
+        rules,
+        tournament_id: None,
+        season_id: None,
+        series_id: None,
+        rule_set,
+        ultimate,
+        players: vec![info.sender.clone()],
+        seats,
+        teams: vec![vec![]],
+        vs_house,
+        resigned: vec![],
+        takebacks: 0,
     };
+    let mut response = Response::new();
+    if vs_house {
+        let house = env.contract.address;
+        game.opponent = house.clone();
+        game.players.push(house.clone());
+        game.teams.push(vec![]);
+        game.bet.amount += amount;
+        game.state = GameState::InProgress;
+        response = response
+            .add_event(events::game_created(&game))
+            .add_event(events::game_joined(&game, &house));
+    } else {
+        response = response.add_event(events::game_created(&game));
+    }
     games().save(deps.storage, U64Key::new(id), &game)?;
 
-    Ok(Response::new()
+    response = response.add_events(collect_fee(
+        deps,
+        &config,
+        &game,
+        &info.sender,
+        referrer,
+        fee,
+    )?);
+
+    Ok(response
         .add_attribute("method", "try_create_game")
         .add_attribute("id", id.to_string()))
 }
 
 pub fn try_cancel_game(
+    env: Env,
     deps: DepsMut,
     info: MessageInfo,
     id: u64,
 ) -> Result<Response, ContractError> {
     assert_is_locked(deps.as_ref())?;
-    games().update(deps.storage, U64Key::new(id), |g| match g {
-        None => Err(ContractError::GameNotFound {}),
-        Some(mut game) => {
-            if game.host != info.sender {
-                Err(ContractError::Unauthorized {})
-            } else if game.state != GameState::New {
-                Err(ContractError::GameCannotBeCancelled {})
-            } else {
-                game.state = GameState::Cancelled;
-                Ok(game)
-            }
-        }
-    })?;
+    let mut game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    if game.host != info.sender {
+        return Err(ContractError::Unauthorized {});
+    } else if game.state != GameState::New {
+        return Err(ContractError::GameCannotBeCancelled {});
+    }
+    let settlement = settle(&game, &info.sender, env.block.time)?;
+    let response = apply_settlement(deps, &mut game, settlement)?;
 
-    Ok(Response::new()
+    Ok(response
+        .add_event(events::game_cancelled(&game))
         .add_attribute("method", "try_cancel_game")
         .add_attribute("id", id.to_string()))
 }
@@ -192,506 +574,5326 @@ pub fn try_join_game(
     deps: DepsMut,
     info: MessageInfo,
     id: u64,
+    referrer: Option<String>,
 ) -> Result<Response, ContractError> {
     assert_is_locked(deps.as_ref())?;
     let config = CONFIG.load(deps.storage)?;
-    let amount = info.funds[0]
-        .amount
-        .multiply_ratio(u128::from(100 - config.fee_percentage), 100u128);
-    games().update(deps.storage, U64Key::new(id), |g| match g {
+    let stake = info
+        .funds
+        .first()
+        .ok_or(ContractError::BetDenomInvalid {})?;
+    let fee = fee_for(deps.storage, &config, stake)?;
+    assert_stake_covers_fee(stake, &fee)?;
+    let amount = stake.amount - fee.amount;
+    let game = games().update(deps.storage, U64Key::new(id), |g| match g {
         None => Err(ContractError::GameNotFound {}),
-        Some(game) if game.host == info.sender => Err(ContractError::NotAllowedToJoin {}),
+        Some(game) if seat_holder(&game, &info.sender).is_some() => {
+            Err(ContractError::NotAllowedToJoin {})
+        }
         Some(game) if game.state != GameState::New => {
             Err(ContractError::NotAllowedInCurrentState { state: game.state })
         }
-        Some(game) if game.bet.amount != amount => Err(ContractError::BetAmounTooLow {}),
-        Some(game) if game.bet.denom != info.funds[0].denom => {
-            Err(ContractError::BetDenomInvalid {})
+        // Every seat is taken for the stake of the host
+        Some(game)
+            if game
+                .bet
+                .amount
+                .multiply_ratio(1u128, game.players.len() as u128)
+                != amount =>
+        {
+            Err(ContractError::BetAmounTooLow {})
         }
+        Some(game) if game.bet.denom != stake.denom => Err(ContractError::BetDenomInvalid {}),
         Some(mut game) => {
-            game.opponent = info.sender.clone();
-            game.state = GameState::InProgress;
-            game.bet.amount = game.bet.amount + amount;
+            if game.players.len() == 1 {
+                game.opponent = info.sender.clone();
+            }
+            game.players.push(info.sender.clone());
+            game.teams.push(vec![]);
+            game.bet.amount += amount;
+            if game.players.len() == usize::from(game.seats) {
+                game.state = GameState::InProgress;
+            }
             game.updated_at = env.block.time;
             Ok(game)
         }
     })?;
+    if game.players.len() > 2 {
+        GAME_MEMBERS.save(deps.storage, (info.sender.clone(), U64Key::new(id)), &id)?;
+    }
 
-    Ok(Response::new()
+    let mut response = Response::new().add_event(events::game_joined(&game, &info.sender));
+    response = response.add_events(collect_fee(
+        deps,
+        &config,
+        &game,
+        &info.sender,
+        referrer,
+        fee,
+    )?);
+
+    Ok(response
         .add_attribute("method", "try_join_game")
         .add_attribute("id", id.to_string())
         .add_attribute("opponent", info.sender.to_string()))
 }
 
-pub fn try_make_move(
+pub fn try_add_teammate(
     env: Env,
     deps: DepsMut,
     info: MessageInfo,
     id: u64,
-    x: u8,
-    y: u8,
+    teammate: String,
 ) -> Result<Response, ContractError> {
     assert_is_locked(deps.as_ref())?;
-    let mut maybe_winner: Option<Addr> = None;
-    let config = CONFIG.load(deps.storage)?;
-    games().update(deps.storage, U64Key::new(id), |g| match g {
+    let teammate = deps.api.addr_validate(&teammate)?;
+    let game = games().update(deps.storage, U64Key::new(id), |g| match g {
         None => Err(ContractError::GameNotFound {}),
-        Some(game) if game.next_player != info.sender.to_string() => {
-            Err(ContractError::NotYourTurn {})
-        }
-        Some(game) if game.state != GameState::InProgress => {
+        Some(game) if game.state != GameState::New => {
             Err(ContractError::NotAllowedInCurrentState { state: game.state })
         }
+        Some(game) if seat_holder(&game, &teammate).is_some() => {
+            Err(ContractError::NotAllowedToJoin {})
+        }
         Some(mut game) => {
-            let pos_x = usize::from(x);
-            let pos_y = usize::from(y);
-            game.grid[pos_x][pos_y] = get_mark_for_cell(&game, pos_x, pos_y)?;
-            if is_game_completed(&game, config.dimension as u16, config.threshold as u16)? {
-                game.state = GameState::Completed;
-                game.winner = game.next_player.clone();
-                maybe_winner = Some(game.next_player.clone());
-            } else {
-                game.next_player = get_next_player(&game)?;
+            let seat = game
+                .players
+                .iter()
+                .position(|p| *p == info.sender)
+                .ok_or(ContractError::Unauthorized {})?;
+            if game.teams[seat].len() >= MAX_TEAMMATES {
+                return Err(ContractError::TeamFull {});
             }
+            game.teams[seat].push(teammate.clone());
             game.updated_at = env.block.time;
-
             Ok(game)
         }
     })?;
+    GAME_MEMBERS.save(deps.storage, (teammate.clone(), U64Key::new(id)), &id)?;
+
+    Ok(Response::new()
+        .add_event(events::teammate_added(&game, &info.sender, &teammate))
+        .add_attribute("method", "try_add_teammate")
+        .add_attribute("id", id.to_string())
+        .add_attribute("teammate", teammate.to_string()))
+}
+
+pub fn try_make_move(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    x: u8,
+    y: u8,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let player = acting_player(deps.as_ref(), &env, &game, &info.sender)?;
+    // Any member of a team can move for its seat
+    if seat_holder(&game, &player).as_ref() != Some(&game.next_player) {
+        return Err(ContractError::NotYourTurn {});
+    } else if game.state != GameState::InProgress {
+        return Err(ContractError::NotAllowedInCurrentState { state: game.state });
+    }
 
-    if maybe_winner != None {
-        try_update_leaderboard(deps, maybe_winner.unwrap())?;
+    let (x, y) = apply_move(&mut game, x, y, env.block.time)?;
+    let mut response =
+        Response::new().add_event(record_move(deps.storage, &env, &game, &player, x, y)?);
+    // The house replies right away, a board full before or after its reply ends in a draw
+    if game.vs_house && game.state == GameState::InProgress {
+        let house = game.next_player.clone();
+        if let Some((x, y)) = house_move(&game)? {
+            let (x, y) = apply_move(&mut game, x, y, env.block.time)?;
+            response = response.add_event(record_move(deps.storage, &env, &game, &house, x, y)?);
+        }
+        if game.state == GameState::InProgress && is_board_full(&game) {
+            game.state = GameState::Drawn;
+        }
     }
+    games().save(deps.storage, U64Key::new(id), &game)?;
+    let response = finish_game(deps, &env, &config, &game, response)?;
 
-    return Ok(Response::new()
-        .add_attribute("method", "try_join_game")
+    Ok(response
+        .add_attribute("method", "try_make_move")
         .add_attribute("id", id.to_string())
-        .add_attribute("opponent", info.sender.to_string()));
+        .add_attribute("player", player.to_string()))
 }
 
-fn try_update_leaderboard(deps: DepsMut, winner: Addr) -> Result<(), ContractError> {
-    LEADERBOARD.update(
-        deps.storage,
-        winner,
-        |maybe_win_count: Option<u64>| -> StdResult<u64> {
-            match maybe_win_count {
-                Some(win_count) => Ok(win_count + 1),
-                None => Ok(1),
+/// Pays out and records the result of `game` when the moves just made on it ended it.
+fn finish_game(
+    mut deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    game: &Game,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    match game.state {
+        GameState::Completed | GameState::PriceWithdrawn => {
+            try_update_leaderboard(deps.branch(), game.winner.clone())?;
+            response = response.add_event(events::game_won(game));
+            if game.state == GameState::PriceWithdrawn {
+                response = add_prize_payout(response, game, &game.winner);
             }
-        },
-    )?;
+            if is_jackpot_eligible(config, game) {
+                let round = get_latest_round(deps.querier, config.terrand_address.to_string())?;
+                let draw = new_draw(game, round);
+                JACKPOT_DRAWS.save(deps.storage, U64Key::new(game.game_id), &draw)?;
+                response = response.add_event(events::jackpot_draw_scheduled(&draw));
+            }
+            response = record_game_result(deps, env, game, response)?;
+        }
+        GameState::Drawn => {
+            response = response.add_event(events::game_drawn(game));
+            response = add_payouts(response, game, split_pot(&game.bet, &active_players(game)));
+            response = record_game_result(deps, env, game, response)?;
+        }
+        _ => {}
+    }
 
-    Ok(())
+    Ok(response)
+}
+
+pub fn try_register_move_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Binary,
+) -> Result<Response, ContractError> {
+    // Compressed or uncompressed secp256k1 public keys
+    if pubkey.len() != 33 && pubkey.len() != 65 {
+        return Err(ContractError::InvalidPublicKey {});
+    }
+    MOVE_KEYS.save(deps.storage, info.sender.clone(), &pubkey)?;
+
+    Ok(Response::new()
+        .add_event(events::move_key_registered(&info.sender))
+        .add_attribute("method", "try_register_move_key"))
 }
 
-pub fn try_withdraw_price(env: Env, deps: DepsMut, id: u64) -> Result<Response, ContractError> {
+/// Replays `moves` made off-chain on the game `id` through the regular move logic, anyone
+/// can submit them. Every move needs the signature of the player who made it and all but the
+/// last one the signatures of every other player as well. A last move signed by its player
+/// alone is how one side moves on when the other one stops countersigning, like any other
+/// move it starts the move timeout for the player to move next.
+pub fn try_submit_signed_moves(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    moves: Vec<SignedMove>,
+) -> Result<Response, ContractError> {
     assert_is_locked(deps.as_ref())?;
-    let game = query_game_by_id(deps.as_ref(), id)?.game;
-    let msg = match game.state {
-        GameState::Completed => {
-            let message = SubMsg::new(BankMsg::Send {
-                to_address: game.winner.to_string(),
-                amount: vec![game.bet],
-            });
-            Ok(message)
+    let config = CONFIG.load(deps.storage)?;
+    let mut game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    if moves.is_empty() {
+        return Err(ContractError::MoveOutOfOrder {});
+    }
+    let keys = active_players(&game)
+        .into_iter()
+        .map(
+            |player| match MOVE_KEYS.may_load(deps.storage, player.clone())? {
+                Some(pubkey) => Ok((player, pubkey)),
+                None => Err(ContractError::MissingSignature {}),
+            },
+        )
+        .collect::<Result<Vec<(Addr, Binary)>, ContractError>>()?;
+
+    let mut response = Response::new();
+    for (i, signed) in moves.iter().enumerate() {
+        if game.state != GameState::InProgress {
+            return Err(ContractError::NotAllowedInCurrentState { state: game.state });
+        } else if signed.takebacks != game.takebacks || signed.move_no != game.move_count + 1 {
+            return Err(ContractError::MoveOutOfOrder {});
         }
-        GameState::InProgress if game.updated_at.seconds() + 3600 < env.block.time.seconds() => {
-            let message = SubMsg::new(BankMsg::Send {
-                to_address: get_next_player(&game)?.to_string(),
-                amount: vec![game.bet],
-            });
-            Ok(message)
+        let last = i + 1 == moves.len();
+        for (player, pubkey) in &keys {
+            let signed_by = is_signed_by(deps.api, &env, id, signed, player, pubkey)?;
+            if !signed_by && (!last || *player == game.next_player) {
+                return Err(ContractError::MissingSignature {});
+            }
         }
-        _ => Err(ContractError::PriceCannotBeWithdrawn {}),
-    }?;
+        let mover = game.next_player.clone();
+        let (x, y) = apply_move(&mut game, signed.x, signed.y, env.block.time)?;
+        response = response.add_event(record_move(deps.storage, &env, &game, &mover, x, y)?);
+    }
+    games().save(deps.storage, U64Key::new(id), &game)?;
 
-    games().update(deps.storage, U64Key::new(game.game_id), |g| match g {
-        None => Err(ContractError::GameNotFound {}),
-        Some(mut game_found) => {
-            game_found.state = GameState::PriceWithdrawn;
-            Ok(game_found)
-        }
-    })?;
+    let response = response.add_event(events::signed_moves_submitted(
+        &game,
+        &info.sender,
+        moves.len(),
+    ));
+    let response = finish_game(deps, &env, &config, &game, response)?;
 
-    return Ok(Response::new()
-        .add_submessage(msg)
-        .add_attribute("method", "try_withdraw_price"));
+    Ok(response
+        .add_attribute("method", "try_submit_signed_moves")
+        .add_attribute("id", id.to_string()))
 }
 
-pub fn try_withdraw_funds(
+/// Takes `player` out of the game `id` when it signed two different moves for the same move
+/// number, whoever caught it can report it.
+pub fn try_report_equivocation(
+    env: Env,
     deps: DepsMut,
-    info: MessageInfo,
-    funds_to_withdraw: Vec<Coin>,
+    id: u64,
+    player: String,
+    first: SignedMove,
+    second: SignedMove,
 ) -> Result<Response, ContractError> {
-    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
-    let msg = SubMsg::new(BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: funds_to_withdraw,
-    });
-
-    return Ok(Response::new()
-        .add_submessage(msg)
-        .add_attribute("method", "try_withdraw_funds")
-    );
+    assert_is_locked(deps.as_ref())?;
+    let player = deps.api.addr_validate(&player)?;
+    let game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let pubkey = match MOVE_KEYS.may_load(deps.storage, player.clone())? {
+        Some(pubkey) if game.players.contains(&player) => pubkey,
+        _ => return Err(ContractError::NoEquivocation {}),
+    };
+    if !is_equivocation(&first, &second)
+        || !is_signed_by(deps.api, &env, id, &first, &player, &pubkey)?
+        || !is_signed_by(deps.api, &env, id, &second, &player, &pubkey)?
+    {
+        return Err(ContractError::NoEquivocation {});
+    }
+    let response = concede(deps, &env, id, &player)?;
+
+    Ok(response
+        .add_event(events::equivocation_reported(id, &player, first.move_no))
+        .add_attribute("method", "try_report_equivocation")
+        .add_attribute("id", id.to_string()))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::IsLocked {} => to_binary(&query_locked(deps)?),
-        QueryMsg::GetAdmin {} => to_binary(&ADMIN.query_admin(deps)?),
-        QueryMsg::GetGames {} => to_binary(&query_games(deps)?),
-        QueryMsg::GetGameById { id } => to_binary(&query_game_by_id(deps, id)?),
-        QueryMsg::GetGamesByAddress { address } => {
-            to_binary(&query_games_by_address(deps, address)?)
+/// Player `sender` acts for in `game`, the player who authorized it when `sender` is a
+/// session key valid for the game and not seated in it itself.
+fn acting_player(deps: Deps, env: &Env, game: &Game, sender: &Addr) -> StdResult<Addr> {
+    if seat_holder(game, sender).is_some() {
+        return Ok(sender.clone());
+    }
+    match SESSION_KEYS.may_load(deps.storage, sender.clone())? {
+        Some(session) if is_session_valid(&session, game.game_id, env.block.time) => {
+            Ok(session.player)
         }
-        QueryMsg::GetLeaderboard {} => to_binary(&query_leaderboard(deps)?),
+        _ => Ok(sender.clone()),
     }
 }
 
-pub fn query_locked(deps: Deps) -> StdResult<LockedResponse> {
-    let state = CONFIG.load(deps.storage)?;
-    Ok(LockedResponse {
-        locked: state.locked,
-    })
+/// Lets `key` move and resign for the sender, in game `game_id` only and for `duration`
+/// seconds when set.
+pub fn try_authorize_session_key(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    key: String,
+    game_id: Option<u64>,
+    duration: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let key = deps.api.addr_validate(&key)?;
+    if key == info.sender {
+        return Err(ContractError::InvalidSessionKey {});
+    }
+    // A key acts for a single player
+    if let Some(session) = SESSION_KEYS.may_load(deps.storage, key.clone())? {
+        if session.player != info.sender {
+            return Err(ContractError::InvalidSessionKey {});
+        }
+    }
+    let session = new_session(info.sender, game_id, duration, env.block.time)?;
+    SESSION_KEYS.save(deps.storage, key.clone(), &session)?;
+
+    Ok(Response::new()
+        .add_event(events::session_key_authorized(&key, &session))
+        .add_attribute("method", "try_authorize_session_key"))
 }
 
-pub fn query_games(deps: Deps) -> StdResult<GamesResponse> {
-    let all_games = games()
-        .range(deps.storage, None, None, Order::Ascending)
-        .flat_map(|item| match item {
-            Ok((_, data)) => Some(data),
-            _ => None,
-        })
-        .collect();
+pub fn try_revoke_session_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    key: String,
+) -> Result<Response, ContractError> {
+    let key = deps.api.addr_validate(&key)?;
+    match SESSION_KEYS.may_load(deps.storage, key.clone())? {
+        Some(session) if session.player == info.sender => {
+            SESSION_KEYS.remove(deps.storage, key.clone())
+        }
+        _ => return Err(ContractError::Unauthorized {}),
+    }
 
-    Ok(GamesResponse { games: all_games })
+    Ok(Response::new()
+        .add_event(events::session_key_revoked(&key, &info.sender))
+        .add_attribute("method", "try_revoke_session_key"))
 }
 
-pub fn query_game_by_id(deps: Deps, id: u64) -> StdResult<GameResponse> {
-    let game = games().load(deps.storage, U64Key::new(id))?;
-    Ok(GameResponse { game })
+/// Marks `(x, y)` for the player to move on `game` and passes the turn on, unless the move
+/// ended the game. Returns the cell the mark landed on.
+fn apply_move(game: &mut Game, x: u8, y: u8, now: Timestamp) -> Result<(u8, u8), ContractError> {
+    let (mut x, mut y) = (x, y);
+    let mut drawn = false;
+    game.move_count += 1;
+    game.pending_offer = None;
+    let winner = if let Some(board) = game.ultimate.as_mut() {
+        let mark = if game.next_player == game.host {
+            1
+        } else {
+            100
+        };
+        match play_ultimate(board, x, y, mark)? {
+            Some(BoardResult::Won) => Some(game.next_player.clone()),
+            Some(BoardResult::Drawn) => {
+                drawn = true;
+                None
+            }
+            None => None,
+        }
+    } else {
+        let (pos_x, pos_y) = target_cell(game, x, y)?;
+        game.grid[pos_x][pos_y] = get_mark_for_cell(game, pos_x, pos_y)?;
+        x = pos_x as u8;
+        y = pos_y as u8;
+        winner_after_move(game, pos_x, pos_y)?
+    };
+    if drawn {
+        game.state = GameState::Drawn;
+    } else if let Some(winner) = winner {
+        declare_winner(game, winner);
+    } else {
+        game.next_player = get_next_player(game)?;
+    }
+    game.updated_at = now;
+
+    Ok((x, y))
 }
 
-pub fn query_games_by_address(deps: Deps, address: String) -> StdResult<GamesResponse> {
-    let addr = deps.api.addr_validate(address.as_str())?;
-    let games_by_host: Vec<Game> = games()
-        .idx
-        .host
-        .prefix(addr.clone())
-        .range(deps.storage, None, None, Order::Ascending)
-        .flat_map(|item| match item {
-            Ok((_, data)) => Some(data),
-            _ => None,
-        })
-        .collect();
+/// Stores the move `player` just made on `game` in its move history.
+fn record_move(
+    storage: &mut dyn Storage,
+    env: &Env,
+    game: &Game,
+    player: &Addr,
+    x: u8,
+    y: u8,
+) -> StdResult<Event> {
+    let move_no = game.move_count;
+    MOVES.save(
+        storage,
+        (U64Key::new(game.game_id), U32Key::new(move_no)),
+        &Move {
+            move_no,
+            player: player.clone(),
+            x,
+            y,
+            time: env.block.time,
+            height: env.block.height,
+        },
+    )?;
 
-    let games_by_opponent: Vec<Game> = games()
+    Ok(events::move_made(game, player, x, y))
+}
+
+pub fn try_resign(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let player = acting_player(deps.as_ref(), &env, &game, &info.sender)?;
+    let response = concede(deps, &env, id, &player)?;
+
+    Ok(response
+        .add_attribute("method", "try_resign")
+        .add_attribute("id", id.to_string())
+        .add_attribute("player", player.to_string()))
+}
+
+/// Takes `player`, who resigned or got caught cheating, out of the game `id`. With two seats
+/// the other player wins, with more the others play on until a single one of them is left.
+fn concede(
+    mut deps: DepsMut,
+    env: &Env,
+    id: u64,
+    player: &Addr,
+) -> Result<Response, ContractError> {
+    let game = games().update(deps.storage, U64Key::new(id), |g| match g {
+        None => Err(ContractError::GameNotFound {}),
+        Some(game) if game.state != GameState::InProgress => {
+            Err(ContractError::NotAllowedInCurrentState { state: game.state })
+        }
+        Some(game) if !active_players(&game).contains(player) => {
+            Err(ContractError::Unauthorized {})
+        }
+        // With more than two seats the resigning player leaves and the others play on until
+        // a single one of them is left
+        Some(mut game) if game.players.len() > 2 => {
+            game.resigned.push(player.clone());
+            let remaining = active_players(&game);
+            if let [winner] = remaining.as_slice() {
+                declare_winner(&mut game, winner.clone());
+            } else if game.next_player == *player {
+                game.next_player = get_next_player(&game)?;
+            }
+            game.updated_at = env.block.time;
+            Ok(game)
+        }
+        Some(mut game) => {
+            let winner = get_opponent_of(&game, player)?;
+            declare_winner(&mut game, winner);
+            game.updated_at = env.block.time;
+            Ok(game)
+        }
+    })?;
+
+    let response = Response::new().add_event(events::game_resigned(&game, player));
+    if game.state == GameState::InProgress {
+        return Ok(response);
+    }
+
+    try_update_leaderboard(deps.branch(), game.winner.clone())?;
+    let mut response = response.add_event(events::game_won(&game));
+    if game.state == GameState::PriceWithdrawn {
+        response = add_prize_payout(response, &game, &game.winner);
+    }
+    record_game_result(deps, env, &game, response)
+}
+
+pub fn try_make_offer(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    kind: OfferKind,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let game = games().update(deps.storage, U64Key::new(id), |g| match g {
+        None => Err(ContractError::GameNotFound {}),
+        Some(game) if game.state != GameState::InProgress => {
+            Err(ContractError::NotAllowedInCurrentState { state: game.state })
+        }
+        Some(mut game) => {
+            get_opponent_of(&game, &info.sender)?;
+            if game.pending_offer.is_some() {
+                return Err(ContractError::OfferAlreadyPending {});
+            }
+            // A tournament game has to produce a winner
+            if kind == OfferKind::Draw && game.tournament_id.is_some() {
+                return Err(ContractError::DrawNotAllowed {});
+            }
+            // Only the player who made the last move can ask to take it back, the forced
+            // sub-board of ultimate tic-tac-toe cannot be rolled back
+            if kind == OfferKind::Takeback
+                && (game.move_count == 0
+                    || game.next_player == info.sender
+                    || game.ultimate.is_some())
+            {
+                return Err(ContractError::TakebackNotAllowed {});
+            }
+            game.pending_offer = Some(Offer {
+                kind: kind.clone(),
+                from: info.sender.clone(),
+            });
+            Ok(game)
+        }
+    })?;
+
+    let offer = game.pending_offer.clone().unwrap();
+    Ok(Response::new()
+        .add_event(events::offer_made(&game, &offer))
+        .add_attribute("method", "try_make_offer")
+        .add_attribute("id", id.to_string())
+        .add_attribute("player", info.sender.to_string()))
+}
+
+pub fn try_accept_draw(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let game = games().update(deps.storage, U64Key::new(id), |g| match g {
+        None => Err(ContractError::GameNotFound {}),
+        Some(mut game) => {
+            take_pending_offer(&mut game, &info.sender, OfferKind::Draw)?;
+            game.state = GameState::Drawn;
+            game.updated_at = env.block.time;
+            Ok(game)
+        }
+    })?;
+
+    let mut response = Response::new().add_event(events::game_drawn(&game));
+    response = add_payouts(response, &game, split_pot(&game.bet, &game.players));
+
+    response = record_game_result(deps, &env, &game, response)?;
+
+    Ok(response
+        .add_attribute("method", "try_accept_draw")
+        .add_attribute("id", id.to_string())
+        .add_attribute("player", info.sender.to_string()))
+}
+
+pub fn try_decline_draw(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut offer: Option<Offer> = None;
+    let game = games().update(deps.storage, U64Key::new(id), |g| match g {
+        None => Err(ContractError::GameNotFound {}),
+        Some(mut game) => {
+            offer = Some(take_pending_offer(
+                &mut game,
+                &info.sender,
+                OfferKind::Draw,
+            )?);
+            Ok(game)
+        }
+    })?;
+
+    Ok(Response::new()
+        .add_event(events::offer_declined(&game, &offer.unwrap()))
+        .add_attribute("method", "try_decline_draw")
+        .add_attribute("id", id.to_string())
+        .add_attribute("player", info.sender.to_string()))
+}
+
+pub fn try_accept_takeback(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let offer = take_pending_offer(&mut game, &info.sender, OfferKind::Takeback)?;
+
+    let key = (U64Key::new(id), U32Key::new(game.move_count));
+    let last_move = MOVES.load(deps.storage, key.clone())?;
+    MOVES.remove(deps.storage, key);
+    game.grid[usize::from(last_move.x)][usize::from(last_move.y)] = 0;
+    game.move_count -= 1;
+    game.takebacks += 1;
+    game.next_player = offer.from;
+    game.updated_at = env.block.time;
+    games().save(deps.storage, U64Key::new(id), &game)?;
+
+    Ok(Response::new()
+        .add_event(events::takeback_accepted(&game, &info.sender))
+        .add_attribute("method", "try_accept_takeback")
+        .add_attribute("id", id.to_string())
+        .add_attribute("player", info.sender.to_string()))
+}
+
+/// Offers the other player of the finished game `id` a rematch for the same stake, which
+/// is held by the contract until the offer is accepted or declined.
+pub fn try_offer_rematch(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    assert_rematch(&game, &info.sender)?;
+    if matches!(&game.pending_offer, Some(offer) if offer.kind == OfferKind::Rematch) {
+        return Err(ContractError::OfferAlreadyPending {});
+    }
+    let fee = rematch_fee(deps.storage, &config, &game, &info.funds)?;
+    let offer = Offer {
+        kind: OfferKind::Rematch,
+        from: info.sender.clone(),
+    };
+    game.pending_offer = Some(offer.clone());
+    games().save(deps.storage, U64Key::new(id), &game)?;
+
+    let mut response = Response::new().add_event(events::offer_made(&game, &offer));
+    response = response.add_events(collect_fee(deps, &config, &game, &info.sender, None, fee)?);
+
+    Ok(response
+        .add_attribute("method", "try_offer_rematch")
+        .add_attribute("id", id.to_string())
+        .add_attribute("player", info.sender.to_string()))
+}
+
+/// Starts the rematch of the finished game `id` with the same stake. The player who moved
+/// second in `id` moves first, both games are linked in the same series.
+pub fn try_accept_rematch(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut previous = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    take_rematch_offer(&mut previous, &info.sender)?;
+    let fee = rematch_fee(deps.storage, &config, &previous, &info.funds)?;
+    let randomness = get_randomness(deps.as_ref().querier, config.terrand_address.to_string())?;
+
+    let series_id = match previous.series_id {
+        Some(series_id) => series_id,
+        None => {
+            let series_id = next_series_id(deps.storage)?;
+            let mut series = Series {
+                series_id,
+                players: vec![previous.host.clone(), previous.opponent.clone()],
+                best_of: None,
+                pot: Coin {
+                    denom: previous.bet.denom.clone(),
+                    amount: Uint128::zero(),
+                },
+                rules: previous.rules,
+                wins: vec![0, 0],
+                state: SeriesState::InProgress,
+                winner: None,
+                games: vec![previous.game_id],
+            };
+            score_game(&mut series, &previous);
+            SERIES.save(deps.storage, U64Key::new(series_id), &series)?;
+            previous.series_id = Some(series_id);
+            series_id
+        }
+    };
+    let game_id = next_id(deps.storage)?;
+    let (grid, ultimate) = new_boards(previous.rule_set, &randomness, &previous.rules);
+    let game = Game {
+        game_id,
+        host: previous.opponent.clone(),
+        bet: previous.bet.clone(),
+        state: GameState::InProgress,
+        grid,
+        opponent: previous.host.clone(),
+        next_player: previous.opponent.clone(),
+        updated_at: env.block.time,
+        winner: Addr::unchecked(""),
+        move_count: 0,
+        pending_offer: None,
+        auto_payout: previous.auto_payout,
+        seed: randomness,
+        rules: previous.rules,
+        tournament_id: None,
+        season_id: None,
+        series_id: Some(series_id),
+        rule_set: previous.rule_set,
+        ultimate,
+        players: vec![previous.opponent.clone(), previous.host.clone()],
+        seats: 2,
+        teams: vec![vec![], vec![]],
+        vs_house: false,
+        resigned: vec![],
+        takebacks: 0,
+    };
+    games().save(deps.storage, U64Key::new(id), &previous)?;
+    games().save(deps.storage, U64Key::new(game_id), &game)?;
+    SERIES.update(
+        deps.storage,
+        U64Key::new(series_id),
+        |series| -> StdResult<_> {
+            let mut series = series.ok_or_else(|| StdError::not_found("Series"))?;
+            series.games.push(game_id);
+            Ok(series)
+        },
+    )?;
+
+    let mut response = Response::new().add_event(events::rematch_started(&previous, &game));
+    response = response.add_events(collect_fee(deps, &config, &game, &info.sender, None, fee)?);
+
+    Ok(response
+        .add_attribute("method", "try_accept_rematch")
+        .add_attribute("id", game_id.to_string())
+        .add_attribute("player", info.sender.to_string()))
+}
+
+/// Withdraws or turns down the rematch offered on `id`, the stake goes back to whoever
+/// offered it.
+pub fn try_decline_rematch(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let offer = match &game.pending_offer {
+        Some(offer) if offer.kind == OfferKind::Rematch => offer.clone(),
+        _ => return Err(ContractError::NoPendingOffer {}),
+    };
+    assert_rematch(&game, &info.sender)?;
+    game.pending_offer = None;
+    games().save(deps.storage, U64Key::new(id), &game)?;
+
+    let stake = rematch_stake(&game);
+    Ok(Response::new()
+        .add_event(events::offer_declined(&game, &offer))
+        .add_event(events::stake_refunded(id, &offer.from, &stake))
+        .add_submessage(SubMsg::new(BankMsg::Send {
+            to_address: offer.from.to_string(),
+            amount: vec![stake],
+        }))
+        .add_attribute("method", "try_decline_rematch")
+        .add_attribute("id", id.to_string())
+        .add_attribute("player", info.sender.to_string()))
+}
+
+/// Takes the rematch offered to `player` off `game`.
+fn take_rematch_offer(game: &mut Game, player: &Addr) -> Result<Offer, ContractError> {
+    assert_rematch(game, player)?;
+    match game.pending_offer.take() {
+        Some(offer) if offer.kind == OfferKind::Rematch && offer.from != *player => Ok(offer),
+        offer => {
+            game.pending_offer = offer;
+            Err(ContractError::NoPendingOffer {})
+        }
+    }
+}
+
+/// Stake each player has in `game` once fees are taken, a rematch is played for as much.
+fn rematch_stake(game: &Game) -> Coin {
+    Coin {
+        denom: game.bet.denom.clone(),
+        amount: game.bet.amount.multiply_ratio(1u128, 2u128),
+    }
+}
+
+/// Checks `funds` cover the stake of a rematch of `game` and returns the fee charged on them.
+fn rematch_fee(
+    storage: &dyn Storage,
+    config: &Config,
+    game: &Game,
+    funds: &[Coin],
+) -> Result<Coin, ContractError> {
+    let stake = match funds {
+        [stake] if stake.denom == game.bet.denom => stake,
+        _ => return Err(ContractError::BetDenomInvalid {}),
+    };
+    let fee = fee_for(storage, config, stake)?;
+    assert_stake_covers_fee(stake, &fee)?;
+    if stake.amount - fee.amount != rematch_stake(game).amount {
+        return Err(ContractError::BetAmounTooLow {});
+    }
+
+    Ok(fee)
+}
+
+/// Records `winner` on `game`. Games created with `auto_payout` are settled right away
+/// instead of waiting for the winner to claim the pot.
+fn declare_winner(game: &mut Game, winner: Addr) {
+    game.winner = winner;
+    // The house never has to claim its winnings
+    game.state = if game.auto_payout || (game.vs_house && game.winner == game.opponent) {
+        GameState::PriceWithdrawn
+    } else {
+        GameState::Completed
+    };
+}
+
+/// Adds the transfer of the pot of `game` to `recipient` to `response`. Tournament games
+/// have no pot of their own, nothing is transferred for them.
+fn add_prize_payout(response: Response, game: &Game, recipient: &Addr) -> Response {
+    add_payouts(response, game, vec![(recipient.clone(), game.bet.clone())])
+}
+
+/// Stores the outcome of `settlement` on `game` and pays out the settled amount.
+fn apply_settlement(
+    deps: DepsMut,
+    game: &mut Game,
+    settlement: Settlement,
+) -> Result<Response, ContractError> {
+    game.state = settlement.state;
+    let mut response = Response::new();
+    if let Some(winner) = settlement.winner {
+        game.winner = winner.clone();
+        response = response.add_event(events::game_won(game));
+        games().save(deps.storage, U64Key::new(game.game_id), game)?;
+        try_update_leaderboard(deps, winner)?;
+    } else {
+        games().save(deps.storage, U64Key::new(game.game_id), game)?;
+    }
+
+    Ok(add_payouts(response, game, settlement.payouts))
+}
+
+/// Pays out `payouts` of `game`, stakes are refunded out of cancelled and drawn games and
+/// prizes paid out of the others. The share of a seat is split among its team, the share of
+/// the house goes back to its bankroll once the result of the game is recorded.
+fn add_payouts(mut response: Response, game: &Game, payouts: Vec<(Addr, Coin)>) -> Response {
+    for (recipient, amount) in share_with_teams(game, payouts) {
+        if amount.amount.is_zero() || (game.vs_house && recipient == game.opponent) {
+            continue;
+        }
+        let event = match game.state {
+            GameState::Cancelled | GameState::Drawn => {
+                events::stake_refunded(game.game_id, &recipient, &amount)
+            }
+            _ => events::prize_paid(game.game_id, &recipient, &amount),
+        };
+        response = response
+            .add_submessage(SubMsg::new(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![amount],
+            }))
+            .add_event(event);
+    }
+    response
+}
+
+/// Removes the offer of `kind` the other player made on `game` so `player` can answer it.
+fn take_pending_offer(
+    game: &mut Game,
+    player: &Addr,
+    kind: OfferKind,
+) -> Result<Offer, ContractError> {
+    if game.state != GameState::InProgress {
+        return Err(ContractError::NotAllowedInCurrentState {
+            state: game.state.clone(),
+        });
+    }
+    get_opponent_of(game, player)?;
+    match game.pending_offer.take() {
+        Some(offer) if offer.kind == kind && offer.from != *player => Ok(offer),
+        _ => Err(ContractError::NoPendingOffer {}),
+    }
+}
+
+fn try_update_leaderboard(deps: DepsMut, winner: Addr) -> Result<(), ContractError> {
+    if let Some(season_id) = CURRENT_SEASON.may_load(deps.storage)? {
+        SEASON_LEADERBOARD.update(
+            deps.storage,
+            (U64Key::new(season_id), winner.clone()),
+            |win_count| -> StdResult<u64> { Ok(win_count.unwrap_or_default() + 1) },
+        )?;
+    }
+    LEADERBOARD.update(
+        deps.storage,
+        winner,
+        |maybe_win_count: Option<u64>| -> StdResult<u64> {
+            match maybe_win_count {
+                Some(win_count) => Ok(win_count + 1),
+                None => Ok(1),
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+pub fn try_withdraw_price(
+    env: Env,
+    mut deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    if game.state == GameState::New {
+        return Err(ContractError::PriceCannotBeWithdrawn {});
+    }
+    // Teammates withdraw for the player holding their seat, anyone can claim the pot of a
+    // stalled house game for the house
+    let caller = match seat_holder(&game, &info.sender) {
+        Some(player) => player,
+        None if game.vs_house => game.opponent.clone(),
+        None => info.sender,
+    };
+    let settlement = settle(&game, &caller, env.block.time)?;
+    let stalled = game.state == GameState::InProgress;
+    let mut response = apply_settlement(deps.branch(), &mut game, settlement)?;
+    // A stalled competition game is won by the waiting player
+    if stalled && game.state == GameState::PriceWithdrawn {
+        response = record_game_result(deps, &env, &game, response)?;
+    }
+
+    Ok(response.add_attribute("method", "try_withdraw_price"))
+}
+
+pub fn try_claim_all(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let unclaimed: Vec<Game> = games()
         .idx
-        .opponent
-        .prefix(addr.clone())
+        .winner
+        .prefix(info.sender.clone())
         .range(deps.storage, None, None, Order::Ascending)
         .flat_map(|item| match item {
             Ok((_, data)) => Some(data),
             _ => None,
         })
+        .filter(|game| game.state == GameState::Completed)
+        .take(limit)
+        .collect();
+
+    if unclaimed.is_empty() {
+        return Err(ContractError::PriceCannotBeWithdrawn {});
+    }
+
+    // Payouts per recipient and denom, teammates of the caller get their shares too
+    let mut payouts: BTreeMap<(String, String), Uint128> = BTreeMap::new();
+    let mut response = Response::new();
+    for mut game in unclaimed.clone() {
+        let settlement = settle(&game, &info.sender, env.block.time)?;
+        for (recipient, payout) in share_with_teams(&game, settlement.payouts) {
+            if payout.amount.is_zero() {
+                continue;
+            }
+            *payouts
+                .entry((recipient.to_string(), payout.denom.clone()))
+                .or_default() += payout.amount;
+            response = response.add_event(events::prize_paid(game.game_id, &recipient, &payout));
+        }
+        game.state = settlement.state;
+        games().save(deps.storage, U64Key::new(game.game_id), &game)?;
+    }
+
+    let game_ids: Vec<String> = unclaimed
+        .iter()
+        .map(|game| game.game_id.to_string())
         .collect();
 
-    let mut games: Vec<Game> = Vec::new();
-    games.extend(games_by_host);
-    games.extend(games_by_opponent);
+    let mut transfers: BTreeMap<String, Vec<Coin>> = BTreeMap::new();
+    for ((recipient, denom), amount) in payouts {
+        transfers
+            .entry(recipient)
+            .or_default()
+            .push(Coin { denom, amount });
+    }
+
+    Ok(response
+        .add_submessages(
+            transfers
+                .into_iter()
+                .map(|(to_address, amount)| SubMsg::new(BankMsg::Send { to_address, amount })),
+        )
+        .add_attribute("method", "try_claim_all")
+        .add_attribute("game_ids", game_ids.join(",")))
+}
+
+/// Credits the referrer of `player` with the configured share of the `fee` paid for `game`
+/// and feeds the jackpot, the rest of the fee goes to the pool handed out by `DistributeFees`.
+fn collect_fee(
+    deps: DepsMut,
+    config: &Config,
+    game: &Game,
+    player: &Addr,
+    referrer: Option<String>,
+    mut fee: Coin,
+) -> Result<Vec<Event>, ContractError> {
+    let mut events = vec![];
+    if let Some(referrer) = register_referral(deps.storage, deps.api, player, referrer)? {
+        let reward = Coin {
+            denom: fee.denom.clone(),
+            amount: fee
+                .amount
+                .multiply_ratio(u128::from(config.referral_percentage), 100u128),
+        };
+        if !reward.amount.is_zero() {
+            credit_referral(deps.storage, &referrer, &reward)?;
+            fee.amount -= reward.amount;
+            events.push(events::referral_credited(game, player, &referrer, &reward));
+        }
+    }
+    let fee = fund_jackpot(deps.storage, config, &fee)?;
+    accrue_fee(deps.storage, &fee)?;
+
+    Ok(events)
+}
+
+pub fn try_claim_referral_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut stats = REFERRAL_STATS
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    if stats.pending_rewards.is_empty() {
+        return Err(ContractError::NoReferralRewards {});
+    }
+    let rewards = std::mem::take(&mut stats.pending_rewards);
+    REFERRAL_STATS.save(deps.storage, info.sender.clone(), &stats)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::new(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: rewards,
+        }))
+        .add_attribute("method", "try_claim_referral_rewards")
+        .add_attribute("referrer", info.sender.to_string()))
+}
+
+pub fn try_update_fee_distribution(
+    deps: DepsMut,
+    info: MessageInfo,
+    shares: Vec<FeeShareMsg>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let shares = shares
+        .into_iter()
+        .map(|share| {
+            Ok(FeeShare {
+                name: share.name,
+                address: deps.api.addr_validate(&share.address)?,
+                weight: share.weight,
+            })
+        })
+        .collect::<StdResult<Vec<FeeShare>>>()?;
+    assert_fee_distribution(&shares)?;
+    FEE_DISTRIBUTION.save(deps.storage, &shares)?;
+
+    Ok(Response::new().add_attribute("method", "try_update_fee_distribution"))
+}
+
+/// Draws the jackpot for the winner of game `id` once the Terrand round of its draw is out,
+/// anyone can trigger it.
+pub fn try_draw_jackpot(deps: DepsMut, id: u64) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let draw = JACKPOT_DRAWS
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::NoJackpotDraw {})?;
+    let terrand = config.terrand_address.to_string();
+    if get_latest_round(deps.querier, terrand.clone())? < draw.round {
+        return Err(ContractError::JackpotDrawPending {});
+    }
+    let randomness = get_round_randomness(deps.querier, terrand, draw.round)?;
+    JACKPOT_DRAWS.remove(deps.storage, U64Key::new(id));
+
+    let hit = lucky_draw(&randomness, id, config.jackpot_odds);
+    let mut response = Response::new().add_event(events::jackpot_drawn(&draw, hit));
+    if hit {
+        if let Some(prize) = take_jackpot(deps.storage, &draw.denom)? {
+            response = response
+                .add_event(events::jackpot_won(&draw, &prize))
+                .add_submessage(SubMsg::new(BankMsg::Send {
+                    to_address: draw.winner.to_string(),
+                    amount: vec![prize],
+                }));
+        }
+    }
+
+    Ok(response
+        .add_attribute("method", "try_draw_jackpot")
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn try_distribute_fees(deps: DepsMut) -> Result<Response, ContractError> {
+    let shares = FEE_DISTRIBUTION
+        .may_load(deps.storage)?
+        .ok_or(ContractError::InvalidFeeDistribution {})?;
+    let pool = FEE_POOL.load(deps.storage)?;
+    if pool.is_empty() {
+        return Err(ContractError::NoFeesToDistribute {});
+    }
+    FEE_POOL.save(deps.storage, &vec![])?;
+
+    let mut response = Response::new();
+    for (share, amount) in shares.iter().zip(split_fees(&pool, &shares)) {
+        if amount.is_empty() {
+            continue;
+        }
+        response = response
+            .add_event(events::fees_distributed(
+                &share.name,
+                &share.address,
+                &amount,
+            ))
+            .add_submessage(SubMsg::new(BankMsg::Send {
+                to_address: share.address.to_string(),
+                amount,
+            }));
+    }
+
+    Ok(response.add_attribute("method", "try_distribute_fees"))
+}
+
+pub fn try_place_bet(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    outcome: BetOutcome,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    // Players, their teammates and their session keys can not bet on their own game
+    let bettor = acting_player(deps.as_ref(), &env, &game, &info.sender)?;
+    if seat_holder(&game, &bettor).is_some() {
+        return Err(ContractError::Unauthorized {});
+    } else if !is_betting_open(&game, config.bets_close_after) {
+        return Err(ContractError::BettingClosed {});
+    }
+    let stake = match info.funds.as_slice() {
+        [stake] if stake.denom == game.bet.denom && !stake.amount.is_zero() => stake,
+        _ => return Err(ContractError::BetDenomInvalid {}),
+    };
+    assert_stake_covers_fee(stake, &fee_for(deps.storage, &config, stake)?)?;
+
+    let mut market = BET_MARKETS
+        .may_load(deps.storage, U64Key::new(id))?
+        .unwrap_or_else(|| BetMarket {
+            game_id: id,
+            denom: game.bet.denom.clone(),
+            pools: BetPosition::default(),
+            fee: None,
+            claimed: Uint128::zero(),
+            paid: Uint128::zero(),
+        });
+    add_stake(&mut market.pools, outcome, stake.amount);
+    BET_MARKETS.save(deps.storage, U64Key::new(id), &market)?;
+    BETS.update(
+        deps.storage,
+        (U64Key::new(id), info.sender.clone()),
+        |position| -> StdResult<_> {
+            let mut position = position.unwrap_or_default();
+            add_stake(&mut position, outcome, stake.amount);
+            Ok(position)
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_event(events::bet_placed(id, &info.sender, outcome, stake))
+        .add_attribute("method", "try_place_bet")
+        .add_attribute("id", id.to_string()))
+}
+
+/// Pays out the side bets of the sender once `id` is over. The first claim on a decided
+/// game takes the house fee out of the pool.
+pub fn try_claim_bet(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let game = games()
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let mut market = BET_MARKETS
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::NoBetToClaim {})?;
+    let resolution = resolve(&game, &market).ok_or(ContractError::BetsNotSettled {})?;
+    let key = (U64Key::new(id), info.sender.clone());
+    let position = BETS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::NoBetToClaim {})?;
+    BETS.remove(deps.storage, key);
+
+    let fee = match (resolution, market.fee) {
+        (Resolution::Refund, _) => Uint128::zero(),
+        (Resolution::Outcome(_), Some(fee)) => fee,
+        (Resolution::Outcome(_), None) => {
+            let config = CONFIG.load(deps.storage)?;
+            let pool = Coin {
+                denom: market.denom.clone(),
+                amount: total_stake(&market.pools),
+            };
+            let mut fee = fee_for(deps.storage, &config, &pool)?;
+            // Every bet covered its fee, only caps raised since can ask for more than the pool
+            fee.amount = fee.amount.min(pool.amount);
+            accrue_fee(deps.storage, &fee)?;
+            market.fee = Some(fee.amount);
+            fee.amount
+        }
+    };
+    let payout = Coin {
+        denom: market.denom.clone(),
+        amount: payout(&market, &position, resolution, fee),
+    };
+    record_claim(&mut market, &position, resolution, payout.amount);
+    BET_MARKETS.save(deps.storage, U64Key::new(id), &market)?;
+
+    let mut response = Response::new().add_event(events::bet_claimed(id, &info.sender, &payout));
+    if !payout.amount.is_zero() {
+        response = response.add_submessage(SubMsg::new(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![payout],
+        }));
+    }
+
+    Ok(response
+        .add_attribute("method", "try_claim_bet")
+        .add_attribute("id", id.to_string()))
+}
+
+/// Opens a best-of series played for the stake sent along, matched by whoever joins it.
+pub fn try_create_series(
+    deps: DepsMut,
+    info: MessageInfo,
+    best_of: u8,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let stake = info
+        .funds
+        .first()
+        .ok_or(ContractError::BetDenomInvalid {})?;
+    assert_host_bet(deps.as_ref(), stake)?;
+    assert_best_of(best_of)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let fee = fee_for(deps.storage, &config, stake)?;
+    assert_stake_covers_fee(stake, &fee)?;
+    let id = next_series_id(deps.storage)?;
+    let series = Series {
+        series_id: id,
+        players: vec![info.sender.clone()],
+        best_of: Some(best_of),
+        pot: Coin {
+            denom: config.min_bet.denom.clone(),
+            amount: stake.amount - fee.amount,
+        },
+        rules: BoardRules {
+            dimension: config.dimension,
+            threshold: config.threshold,
+        },
+        wins: vec![0, 0],
+        state: SeriesState::Open,
+        winner: None,
+        games: vec![],
+    };
+    SERIES.save(deps.storage, U64Key::new(id), &series)?;
+    let fee = fund_jackpot(deps.storage, &config, &fee)?;
+    accrue_fee(deps.storage, &fee)?;
+
+    Ok(Response::new()
+        .add_event(events::series_created(&series))
+        .add_attribute("method", "try_create_series")
+        .add_attribute("id", id.to_string()))
+}
+
+/// Matches the stake of the open series `id` and starts its first game, the host of the
+/// series moves first.
+pub fn try_join_series(
+    env: Env,
+    mut deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut series = SERIES
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::SeriesNotFound {})?;
+    let stake = info
+        .funds
+        .first()
+        .ok_or(ContractError::BetDenomInvalid {})?;
+    let fee = fee_for(deps.storage, &config, stake)?;
+    assert_stake_covers_fee(stake, &fee)?;
+    let amount = stake.amount - fee.amount;
+    if series.state != SeriesState::Open {
+        return Err(ContractError::RegistrationClosed {});
+    } else if series.players.contains(&info.sender) {
+        return Err(ContractError::NotAllowedToJoin {});
+    } else if series.pot.denom != stake.denom {
+        return Err(ContractError::BetDenomInvalid {});
+    } else if series.pot.amount != amount {
+        return Err(ContractError::BetAmounTooLow {});
+    }
+    series.players.push(info.sender.clone());
+    series.pot.amount += amount;
+    series.state = SeriesState::InProgress;
+    let fee = fund_jackpot(deps.storage, &config, &fee)?;
+    accrue_fee(deps.storage, &fee)?;
+
+    let pairing = (series.players[0].clone(), info.sender.clone());
+    let response = Response::new().add_event(events::series_started(&series));
+    let response = start_series_game(deps.branch(), &env, &mut series, pairing, response)?;
+    SERIES.save(deps.storage, U64Key::new(id), &series)?;
+
+    Ok(response
+        .add_attribute("method", "try_join_series")
+        .add_attribute("id", id.to_string())
+        .add_attribute("opponent", info.sender.to_string()))
+}
+
+pub fn try_cancel_series(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut series = SERIES
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::SeriesNotFound {})?;
+    if series.players[0] != info.sender {
+        return Err(ContractError::Unauthorized {});
+    } else if series.state != SeriesState::Open {
+        return Err(ContractError::RegistrationClosed {});
+    }
+    series.state = SeriesState::Cancelled;
+    SERIES.save(deps.storage, U64Key::new(id), &series)?;
+
+    Ok(Response::new()
+        .add_event(events::series_cancelled(&series))
+        .add_submessage(SubMsg::new(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![series.pot],
+        }))
+        .add_attribute("method", "try_cancel_series")
+        .add_attribute("id", id.to_string()))
+}
+
+/// Spawns the next game of `series` between the players of `pairing`.
+fn start_series_game(
+    deps: DepsMut,
+    env: &Env,
+    series: &mut Series,
+    (host, opponent): (Addr, Addr),
+    response: Response,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let randomness = get_randomness(deps.as_ref().querier, config.terrand_address.to_string())?;
+
+    let mut game = stake_free_game(
+        next_id(deps.storage)?,
+        host,
+        opponent,
+        &series.pot.denom,
+        series.rules,
+        &randomness,
+        env.block.time,
+    );
+    game.series_id = Some(series.series_id);
+    games().save(deps.storage, U64Key::new(game.game_id), &game)?;
+    series.games.push(game.game_id);
+
+    Ok(response.add_event(events::series_game_started(series, &game)))
+}
+
+/// Scores the series game `game`. Clinching a best-of series pays out its pot, otherwise
+/// the next game is started.
+fn advance_series(
+    mut deps: DepsMut,
+    env: &Env,
+    game: &Game,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    let id = match game.series_id {
+        None => return Ok(response),
+        Some(id) => id,
+    };
+    let mut series = SERIES.load(deps.storage, U64Key::new(id))?;
+    if let Some(winner) = score_game(&mut series, game) {
+        response = response.add_event(events::series_won(&series));
+        if !series.pot.amount.is_zero() {
+            response = response.add_submessage(SubMsg::new(BankMsg::Send {
+                to_address: winner.to_string(),
+                amount: vec![series.pot.clone()],
+            }));
+        }
+    } else if series.best_of.is_some() {
+        response = start_series_game(
+            deps.branch(),
+            env,
+            &mut series,
+            next_pairing(game),
+            response,
+        )?;
+    }
+    SERIES.save(deps.storage, U64Key::new(id), &series)?;
+
+    Ok(response)
+}
+
+pub fn try_create_tournament(
+    deps: DepsMut,
+    info: MessageInfo,
+    entry_fee: Coin,
+    bracket_size: u32,
+    rules: BoardRules,
+    prize_distribution: Vec<u16>,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    assert_host_bet(deps.as_ref(), &entry_fee)?;
+    assert_board_rules(&rules)?;
+    assert_tournament(bracket_size, &prize_distribution)?;
+
+    let id = next_tournament_id(deps.storage)?;
+    let tournament = Tournament {
+        tournament_id: id,
+        creator: info.sender,
+        entry_fee,
+        bracket_size,
+        rules,
+        prize_distribution,
+        state: TournamentState::Registration,
+        players: vec![],
+        round: 0,
+        bracket: vec![],
+        games: vec![],
+        winners: vec![],
+        eliminated: vec![],
+    };
+    TOURNAMENTS.save(deps.storage, U64Key::new(id), &tournament)?;
+
+    Ok(Response::new()
+        .add_event(events::tournament_created(&tournament))
+        .add_attribute("method", "try_create_tournament")
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn try_register_for_tournament(
+    env: Env,
+    mut deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut tournament = TOURNAMENTS
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::TournamentNotFound {})?;
+    if tournament.state != TournamentState::Registration {
+        return Err(ContractError::RegistrationClosed {});
+    } else if tournament.players.contains(&info.sender) {
+        return Err(ContractError::AlreadyRegistered {});
+    } else if info.funds != vec![tournament.entry_fee.clone()] {
+        return Err(ContractError::EntryFeeInvalid {});
+    }
+    tournament.players.push(info.sender.clone());
+
+    let mut response =
+        Response::new().add_event(events::tournament_registered(&tournament, &info.sender));
+    // The bracket is seeded as soon as the last seat is taken
+    if tournament.players.len() as u32 == tournament.bracket_size {
+        let config = CONFIG.load(deps.storage)?;
+        let randomness = get_randomness(deps.as_ref().querier, config.terrand_address.to_string())?;
+        tournament.bracket = seed_bracket(&tournament.players, &randomness);
+        tournament.state = TournamentState::InProgress;
+        let pairings = pairings(&tournament.bracket);
+        response = start_round(deps.branch(), &env, &mut tournament, pairings, response)?;
+    }
+    TOURNAMENTS.save(deps.storage, U64Key::new(id), &tournament)?;
+
+    Ok(response
+        .add_attribute("method", "try_register_for_tournament")
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn try_cancel_tournament(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let mut tournament = TOURNAMENTS
+        .may_load(deps.storage, U64Key::new(id))?
+        .ok_or(ContractError::TournamentNotFound {})?;
+    if tournament.creator != info.sender && !ADMIN.is_admin(deps.as_ref(), &info.sender)? {
+        return Err(ContractError::Unauthorized {});
+    } else if tournament.state != TournamentState::Registration {
+        return Err(ContractError::RegistrationClosed {});
+    }
+    tournament.state = TournamentState::Cancelled;
+    TOURNAMENTS.save(deps.storage, U64Key::new(id), &tournament)?;
+
+    let mut response = Response::new().add_event(events::tournament_cancelled(&tournament));
+    for player in &tournament.players {
+        response = response.add_submessage(SubMsg::new(BankMsg::Send {
+            to_address: player.to_string(),
+            amount: vec![tournament.entry_fee.clone()],
+        }));
+    }
+
+    Ok(response
+        .add_attribute("method", "try_cancel_tournament")
+        .add_attribute("id", id.to_string()))
+}
+
+/// Spawns the games of the next round of `tournament` between the players of `pairings`.
+/// Tournament games have no pot of their own, the entry fees are paid out once the final
+/// is decided.
+fn start_round(
+    deps: DepsMut,
+    env: &Env,
+    tournament: &mut Tournament,
+    pairings: Vec<(Addr, Addr)>,
+    response: Response,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let randomness = get_randomness(deps.as_ref().querier, config.terrand_address.to_string())?;
+
+    tournament.games.clear();
+    tournament.winners = vec![None; pairings.len()];
+    for (host, opponent) in pairings {
+        let mut game = stake_free_game(
+            next_id(deps.storage)?,
+            host,
+            opponent,
+            &tournament.entry_fee.denom,
+            tournament.rules,
+            &randomness,
+            env.block.time,
+        );
+        game.tournament_id = Some(tournament.tournament_id);
+        games().save(deps.storage, U64Key::new(game.game_id), &game)?;
+        tournament.games.push(game.game_id);
+    }
+
+    Ok(response.add_event(events::tournament_round_started(tournament)))
+}
+
+/// A game of a tournament or league season, played without a pot of its own.
+fn stake_free_game(
+    game_id: u64,
+    host: Addr,
+    opponent: Addr,
+    denom: &str,
+    rules: BoardRules,
+    randomness: &Binary,
+    now: Timestamp,
+) -> Game {
+    Game {
+        game_id,
+        host: host.clone(),
+        bet: Coin {
+            denom: denom.to_string(),
+            amount: Uint128::zero(),
+        },
+        state: GameState::InProgress,
+        grid: new_grid(randomness, &rules),
+        opponent: opponent.clone(),
+        next_player: host.clone(),
+        updated_at: now,
+        winner: Addr::unchecked(""),
+        move_count: 0,
+        pending_offer: None,
+        auto_payout: true,
+        seed: randomness.clone(),
+        rules,
+        tournament_id: None,
+        season_id: None,
+        series_id: None,
+        rule_set: RuleSet::Classic,
+        ultimate: None,
+        players: vec![host, opponent],
+        seats: 2,
+        teams: vec![vec![], vec![]],
+        vs_house: false,
+        resigned: vec![],
+        takebacks: 0,
+    }
+}
+
+/// Updates the tournament, series or league season `game` was played in with its result.
+fn record_game_result(
+    mut deps: DepsMut,
+    env: &Env,
+    game: &Game,
+    response: Response,
+) -> Result<Response, ContractError> {
+    if game.vs_house {
+        HOUSE.update(deps.storage, &game.bet.denom, |house| -> StdResult<_> {
+            let mut house = house.unwrap_or_default();
+            settle_stake(&mut house, game);
+            Ok(house)
+        })?;
+    }
+    let response = advance_tournament(deps.branch(), env, game, response)?;
+    let response = advance_series(deps.branch(), env, game, response)?;
+    score_league_game(deps, env, game, response)
+}
+
+/// Moves the winner of the tournament game `game` on. Completing a round starts the next
+/// one, completing the final pays out the prize pool.
+fn advance_tournament(
+    mut deps: DepsMut,
+    env: &Env,
+    game: &Game,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    let id = match game.tournament_id {
+        None => return Ok(response),
+        Some(id) => id,
+    };
+    let mut tournament = TOURNAMENTS.load(deps.storage, U64Key::new(id))?;
+    if record_winner(&mut tournament, game.game_id, &game.winner)? {
+        let pairings = close_round(&mut tournament);
+        if tournament.state == TournamentState::Completed {
+            response = response.add_event(events::tournament_completed(&tournament));
+            for (recipient, prize) in prize_payouts(&tournament) {
+                response = response
+                    .add_submessage(SubMsg::new(BankMsg::Send {
+                        to_address: recipient.to_string(),
+                        amount: vec![prize.clone()],
+                    }))
+                    .add_event(events::tournament_prize_paid(
+                        &tournament,
+                        &recipient,
+                        &prize,
+                    ));
+            }
+        } else {
+            response = start_round(deps.branch(), env, &mut tournament, pairings, response)?;
+        }
+    }
+    TOURNAMENTS.save(deps.storage, U64Key::new(id), &tournament)?;
+
+    Ok(response)
+}
+
+/// Ends the running leaderboard season, paying out its reward pool to the top players, and
+/// starts the next one. Funds sent along are added to the reward pool of the new season,
+/// together with whatever the last season did not pay out.
+pub fn try_start_season(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    reward_distribution: Vec<u16>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let mut response = Response::new();
+    let mut reward_pool = vec![];
+
+    let current = CURRENT_SEASON.may_load(deps.storage)?;
+    if let Some(season_id) = current {
+        let mut season = LEADERBOARD_SEASONS.load(deps.storage, U64Key::new(season_id))?;
+        let mut entries = season_leaderboard(deps.as_ref(), season_id)?;
+        rank_entries(&mut entries);
+        let (results, leftover) =
+            season_rewards(&season.reward_pool, &season.reward_distribution, &entries);
+        season.ended_at = Some(env.block.time);
+        season.results = results;
+        LEADERBOARD_SEASONS.save(deps.storage, U64Key::new(season_id), &season)?;
+
+        response = response.add_event(events::leaderboard_season_ended(&season));
+        for result in season.results.iter().filter(|r| !r.reward.is_empty()) {
+            response = response
+                .add_submessage(SubMsg::new(BankMsg::Send {
+                    to_address: result.player.to_string(),
+                    amount: result.reward.clone(),
+                }))
+                .add_event(events::season_reward_paid(&season, result));
+        }
+        reward_pool = leftover;
+    }
+    for coin in &info.funds {
+        add_coin(&mut reward_pool, coin);
+    }
+    assert_reward_distribution(&reward_pool, &reward_distribution)?;
+
+    let season = LeaderboardSeason {
+        season_id: current.unwrap_or_default() + 1,
+        started_at: env.block.time,
+        ended_at: None,
+        reward_pool,
+        reward_distribution,
+        results: vec![],
+    };
+    LEADERBOARD_SEASONS.save(deps.storage, U64Key::new(season.season_id), &season)?;
+    CURRENT_SEASON.save(deps.storage, &season.season_id)?;
+
+    Ok(response
+        .add_event(events::leaderboard_season_started(&season))
+        .add_attribute("method", "try_start_season")
+        .add_attribute("season_id", season.season_id.to_string()))
+}
+
+fn season_leaderboard(deps: Deps, season_id: u64) -> StdResult<Vec<LeaderBoardEntry>> {
+    SEASON_LEADERBOARD
+        .prefix(U64Key::new(season_id))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, win_count) = item?;
+            let player = Addr::unchecked(String::from_utf8(key)?);
+            Ok(LeaderBoardEntry { player, win_count })
+        })
+        .collect()
+}
+
+pub fn try_configure_league(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    season_duration: u64,
+    rules: BoardRules,
+    points: LeaguePoints,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    assert_board_rules(&rules)?;
+    if season_duration == 0 {
+        return Err(ContractError::InvalidSeasonDuration {});
+    }
+
+    // Later settings only apply to seasons opened from now on
+    let league = match LEAGUE.may_load(deps.storage)? {
+        Some(league) => League {
+            season_duration,
+            rules,
+            points,
+            ..league
+        },
+        None => {
+            let league = League {
+                season_duration,
+                rules,
+                points,
+                next_season: 1,
+            };
+            open_season(
+                deps.storage,
+                &league,
+                env.block.time.plus_seconds(season_duration),
+            )?;
+            league
+        }
+    };
+    LEAGUE.save(deps.storage, &league)?;
+
+    Ok(Response::new()
+        .add_event(events::league_configured(&league))
+        .add_attribute("method", "try_configure_league"))
+}
+
+pub fn try_join_league_season(
+    env: Env,
+    mut deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    let response = roll_league(deps.branch(), &env, Response::new())?;
+    let league = LEAGUE
+        .may_load(deps.storage)?
+        .ok_or(ContractError::LeagueNotConfigured {})?;
+    let mut season = LEAGUE_SEASONS.load(deps.storage, U64Key::new(league.next_season))?;
+    if season.players.contains(&info.sender) {
+        return Err(ContractError::AlreadyRegistered {});
+    } else if season.players.len() >= MAX_LEAGUE_PLAYERS {
+        return Err(ContractError::SeasonFull {});
+    }
+    season.players.push(info.sender.clone());
+    LEAGUE_SEASONS.save(deps.storage, U64Key::new(season.season_id), &season)?;
+
+    Ok(response
+        .add_event(events::league_season_joined(&season, &info.sender))
+        .add_attribute("method", "try_join_league_season")
+        .add_attribute("season_id", season.season_id.to_string()))
+}
+
+pub fn try_advance_league(env: Env, deps: DepsMut) -> Result<Response, ContractError> {
+    assert_is_locked(deps.as_ref())?;
+    if LEAGUE.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::LeagueNotConfigured {});
+    }
+    let response = roll_league(deps, &env, Response::new())?;
+
+    Ok(response.add_attribute("method", "try_advance_league"))
+}
+
+/// Opens season `league.next_season` for registration until `start`.
+fn open_season(
+    storage: &mut dyn Storage,
+    league: &League,
+    start: Timestamp,
+) -> StdResult<LeagueSeason> {
+    let season = LeagueSeason {
+        season_id: league.next_season,
+        start,
+        end: start.plus_seconds(league.season_duration),
+        rules: league.rules,
+        points: league.points,
+        state: SeasonState::Registration,
+        players: vec![],
+        games: vec![],
+    };
+    LEAGUE_SEASONS.save(storage, U64Key::new(season.season_id), &season)?;
+    Ok(season)
+}
+
+/// Starts the season open for registration once its start time has passed: the previous
+/// season is finished, the games of every pair of registered players are scheduled and
+/// the next season is opened for registration.
+fn roll_league(
+    mut deps: DepsMut,
+    env: &Env,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    let mut league = match LEAGUE.may_load(deps.storage)? {
+        None => return Ok(response),
+        Some(league) => league,
+    };
+    let mut season = LEAGUE_SEASONS.load(deps.storage, U64Key::new(league.next_season))?;
+    if env.block.time < season.start {
+        return Ok(response);
+    }
+
+    let previous = LEAGUE_SEASONS.may_load(deps.storage, U64Key::new(season.season_id - 1))?;
+    if let Some(mut previous) = previous {
+        if previous.state == SeasonState::InProgress {
+            previous.state = SeasonState::Finished;
+            LEAGUE_SEASONS.save(deps.storage, U64Key::new(previous.season_id), &previous)?;
+            response = response.add_event(events::league_season_finished(&previous));
+        }
+    }
+
+    // Nobody advanced the league for a whole season, the missed one is over before it began
+    let next_start = if env.block.time < season.end {
+        season.state = SeasonState::InProgress;
+        response = start_league_season(deps.branch(), env, &mut season, response)?;
+        season.end
+    } else {
+        season.state = SeasonState::Finished;
+        response = response.add_event(events::league_season_finished(&season));
+        env.block.time.plus_seconds(league.season_duration)
+    };
+    LEAGUE_SEASONS.save(deps.storage, U64Key::new(season.season_id), &season)?;
+
+    league.next_season += 1;
+    open_season(deps.storage, &league, next_start)?;
+    LEAGUE.save(deps.storage, &league)?;
+
+    Ok(response)
+}
+
+/// Schedules a game for every pair of players registered for `season`.
+fn start_league_season(
+    deps: DepsMut,
+    env: &Env,
+    season: &mut LeagueSeason,
+    response: Response,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let randomness = get_randomness(deps.as_ref().querier, config.terrand_address.to_string())?;
+
+    for player in &season.players {
+        let standing = Standing {
+            player: player.clone(),
+            played: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            points: 0,
+        };
+        STANDINGS.save(
+            deps.storage,
+            (U64Key::new(season.season_id), player.clone()),
+            &standing,
+        )?;
+    }
+    for (host, opponent) in round_robin(&season.players) {
+        let mut game = stake_free_game(
+            next_id(deps.storage)?,
+            host,
+            opponent,
+            &config.min_bet.denom,
+            season.rules,
+            &randomness,
+            env.block.time,
+        );
+        game.season_id = Some(season.season_id);
+        games().save(deps.storage, U64Key::new(game.game_id), &game)?;
+        season.games.push(game.game_id);
+    }
+
+    Ok(response.add_event(events::league_season_started(season)))
+}
+
+/// Adds the result of the league game `game` to the standings of its season. Games decided
+/// after the season ended do not count.
+fn score_league_game(
+    mut deps: DepsMut,
+    env: &Env,
+    game: &Game,
+    response: Response,
+) -> Result<Response, ContractError> {
+    let season_id = match game.season_id {
+        None => return Ok(response),
+        Some(season_id) => season_id,
+    };
+    let response = roll_league(deps.branch(), env, response)?;
+    let season = LEAGUE_SEASONS.load(deps.storage, U64Key::new(season_id))?;
+    if season.state != SeasonState::InProgress || env.block.time >= season.end {
+        return Ok(response);
+    }
+
+    let outcomes = if game.state == GameState::Drawn {
+        vec![
+            (game.host.clone(), Outcome::Draw),
+            (game.opponent.clone(), Outcome::Draw),
+        ]
+    } else {
+        vec![
+            (game.winner.clone(), Outcome::Win),
+            (get_opponent_of(game, &game.winner)?, Outcome::Loss),
+        ]
+    };
+    for (player, outcome) in outcomes {
+        STANDINGS.update(
+            deps.storage,
+            (U64Key::new(season_id), player),
+            |standing| -> Result<_, ContractError> {
+                let mut standing = standing.ok_or_else(|| StdError::not_found("Standing"))?;
+                apply_outcome(&mut standing, outcome, &season.points);
+                Ok(standing)
+            },
+        )?;
+    }
+
+    Ok(response)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::IsLocked {} => to_binary(&query_locked(deps)?),
+        QueryMsg::GetAdmin {} => to_binary(&ADMIN.query_admin(deps)?),
+        QueryMsg::GetGames { rule_set } => to_binary(&query_games(deps, rule_set)?),
+        QueryMsg::GetGameById { id } => to_binary(&query_game_by_id(deps, id)?),
+        QueryMsg::GetGamesByAddress { address, rule_set } => {
+            to_binary(&query_games_by_address(deps, address, rule_set)?)
+        }
+        QueryMsg::GetLeaderboard {} => to_binary(&query_leaderboard(deps)?),
+        QueryMsg::GetMoves {
+            game_id,
+            start_after,
+            limit,
+        } => to_binary(&query_moves(deps, game_id, start_after, limit)?),
+        QueryMsg::GetReferralStats { address } => to_binary(&query_referral_stats(deps, address)?),
+        QueryMsg::GetPendingDistribution {} => to_binary(&query_pending_distribution(deps)?),
+        QueryMsg::GetJackpot {} => to_binary(&query_jackpot(deps)?),
+        QueryMsg::GetJackpotDraw { game_id } => to_binary(&query_jackpot_draw(deps, game_id)?),
+        QueryMsg::GetHouse { denom } => to_binary(&query_house(deps, denom)?),
+        QueryMsg::GetSessionKey { key } => to_binary(&query_session_key(deps, key)?),
+        QueryMsg::GetMoveKey { address } => to_binary(&query_move_key(deps, address)?),
+        QueryMsg::GetHouseShares { denom, address } => {
+            to_binary(&query_house_shares(deps, denom, address)?)
+        }
+        QueryMsg::GetSeries { series_id } => to_binary(&query_series(deps, series_id)?),
+        QueryMsg::GetTournament { tournament_id } => {
+            to_binary(&query_tournament(deps, tournament_id)?)
+        }
+        QueryMsg::GetTournaments { start_after, limit } => {
+            to_binary(&query_tournaments(deps, start_after, limit)?)
+        }
+        QueryMsg::GetBetMarket { game_id } => to_binary(&query_bet_market(deps, game_id)?),
+        QueryMsg::GetBet { game_id, address } => to_binary(&query_bet(deps, game_id, address)?),
+        QueryMsg::GetSeasonLeaderboard { season_id } => {
+            to_binary(&query_season_leaderboard(deps, season_id)?)
+        }
+        QueryMsg::GetLeaderboardSeason { season_id } => {
+            to_binary(&query_leaderboard_season(deps, season_id)?)
+        }
+        QueryMsg::GetLeague {} => to_binary(&query_league(deps)?),
+        QueryMsg::GetStandings { season_id } => to_binary(&query_standings(deps, season_id)?),
+    }
+}
+
+pub fn query_locked(deps: Deps) -> StdResult<LockedResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    Ok(LockedResponse {
+        locked: state.locked,
+    })
+}
+
+pub fn query_games(deps: Deps, rule_set: Option<RuleSet>) -> StdResult<GamesResponse> {
+    let all_games = games()
+        .range(deps.storage, None, None, Order::Ascending)
+        .flat_map(|item| match item {
+            Ok((_, data)) => Some(data),
+            _ => None,
+        })
+        .filter(|game| is_variant(game, rule_set))
+        .collect();
+
+    Ok(GamesResponse { games: all_games })
+}
+
+pub fn query_game_by_id(deps: Deps, id: u64) -> StdResult<GameResponse> {
+    let game = games().load(deps.storage, U64Key::new(id))?;
+    Ok(GameResponse { game })
+}
+
+pub fn query_games_by_address(
+    deps: Deps,
+    address: String,
+    rule_set: Option<RuleSet>,
+) -> StdResult<GamesResponse> {
+    let addr = deps.api.addr_validate(address.as_str())?;
+    let games_by_host: Vec<Game> = games()
+        .idx
+        .host
+        .prefix(addr.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .flat_map(|item| match item {
+            Ok((_, data)) => Some(data),
+            _ => None,
+        })
+        .collect();
+
+    let games_by_opponent: Vec<Game> = games()
+        .idx
+        .opponent
+        .prefix(addr.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .flat_map(|item| match item {
+            Ok((_, data)) => Some(data),
+            _ => None,
+        })
+        .collect();
+
+    // Later seats and teammates have an index of their own
+    let games_by_member: Vec<Game> = GAME_MEMBERS
+        .prefix(addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.and_then(|(_, id)| games().load(deps.storage, U64Key::new(id))))
+        .collect::<StdResult<Vec<Game>>>()?;
+
+    // A game is listed once, whichever ways the address takes part in it
+    let mut games: BTreeMap<u64, Game> = BTreeMap::new();
+    for game in games_by_host
+        .into_iter()
+        .chain(games_by_opponent)
+        .chain(games_by_member)
+    {
+        if is_variant(&game, rule_set) {
+            games.insert(game.game_id, game);
+        }
+    }
+
+    Ok(GamesResponse {
+        games: games.into_iter().map(|(_, game)| game).collect(),
+    })
+}
+
+/// Whether `game` is played by `rule_set`, every game matches when no variant is asked for.
+fn is_variant(game: &Game, rule_set: Option<RuleSet>) -> bool {
+    rule_set.map_or(true, |rule_set| game.rule_set == rule_set)
+}
+
+pub fn query_leaderboard(deps: Deps) -> StdResult<LeaderboardResponse> {
+    let leaderboard_entries = LEADERBOARD
+        .range(deps.storage, None, None, Order::Descending)
+        .map(|item| {
+            let (k, win_count) = item.unwrap();
+            let address = match std::str::from_utf8(&k) {
+                Ok(v) => v,
+                Err(e) => panic!("Invalid UTF-8 sequence: {}", e),
+            };
+            let player = Addr::unchecked(address);
+            LeaderBoardEntry { player, win_count }
+        })
+        .collect();
+
+    Ok(LeaderboardResponse {
+        entries: leaderboard_entries,
+    })
+}
+
+pub fn query_moves(
+    deps: Deps,
+    game_id: u64,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<MovesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let moves = MOVES
+        .prefix(U64Key::new(game_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, mv)| mv))
+        .collect::<StdResult<Vec<Move>>>()?;
+
+    Ok(MovesResponse { moves })
+}
+
+pub fn query_referral_stats(deps: Deps, address: String) -> StdResult<ReferralStatsResponse> {
+    let addr = deps.api.addr_validate(address.as_str())?;
+    let referrer = REFERRERS.may_load(deps.storage, addr.clone())?;
+    let stats = REFERRAL_STATS
+        .may_load(deps.storage, addr)?
+        .unwrap_or_default();
+
+    Ok(ReferralStatsResponse {
+        referrer,
+        referred_players: stats.referred_players,
+        pending_rewards: stats.pending_rewards,
+        total_earned: stats.total_earned,
+    })
+}
+
+pub fn query_pending_distribution(deps: Deps) -> StdResult<PendingDistributionResponse> {
+    let fee_pool = FEE_POOL.load(deps.storage)?;
+    let shares = FEE_DISTRIBUTION.may_load(deps.storage)?.unwrap_or_default();
+    let amounts = split_fees(&fee_pool, &shares);
+    let shares = shares
+        .into_iter()
+        .zip(amounts)
+        .map(|(share, amount)| PendingShare {
+            name: share.name,
+            address: share.address,
+            weight: share.weight,
+            amount,
+        })
+        .collect();
+
+    Ok(PendingDistributionResponse { fee_pool, shares })
+}
+
+pub fn query_series(deps: Deps, id: u64) -> StdResult<SeriesResponse> {
+    let series = SERIES.load(deps.storage, U64Key::new(id))?;
+    Ok(SeriesResponse { series })
+}
+
+pub fn query_jackpot_draw(deps: Deps, game_id: u64) -> StdResult<JackpotDrawResponse> {
+    Ok(JackpotDrawResponse {
+        draw: JACKPOT_DRAWS.may_load(deps.storage, U64Key::new(game_id))?,
+    })
+}
+
+pub fn query_tournament(deps: Deps, id: u64) -> StdResult<TournamentResponse> {
+    let tournament = TOURNAMENTS.load(deps.storage, U64Key::new(id))?;
+    Ok(TournamentResponse { tournament })
+}
+
+pub fn query_tournaments(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TournamentsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let tournaments = TOURNAMENTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, tournament)| tournament))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TournamentsResponse { tournaments })
+}
+
+pub fn query_bet_market(deps: Deps, id: u64) -> StdResult<BetMarketResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let market = BET_MARKETS.load(deps.storage, U64Key::new(id))?;
+    let game = games().load(deps.storage, U64Key::new(id))?;
+    Ok(BetMarketResponse {
+        market,
+        open: is_betting_open(&game, config.bets_close_after),
+    })
+}
+
+pub fn query_bet(deps: Deps, id: u64, address: String) -> StdResult<BetResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let position = BETS
+        .may_load(deps.storage, (U64Key::new(id), address))?
+        .unwrap_or_default();
+    Ok(BetResponse { position })
+}
+
+pub fn query_season_leaderboard(
+    deps: Deps,
+    season_id: Option<u64>,
+) -> StdResult<LeaderboardResponse> {
+    let season_id = match season_id {
+        Some(season_id) => Some(season_id),
+        None => CURRENT_SEASON.may_load(deps.storage)?,
+    };
+    let mut entries = match season_id {
+        Some(season_id) => season_leaderboard(deps, season_id)?,
+        None => vec![],
+    };
+    rank_entries(&mut entries);
+
+    Ok(LeaderboardResponse { entries })
+}
+
+pub fn query_leaderboard_season(deps: Deps, id: u64) -> StdResult<LeaderboardSeasonResponse> {
+    let season = LEADERBOARD_SEASONS.load(deps.storage, U64Key::new(id))?;
+    Ok(LeaderboardSeasonResponse { season })
+}
+
+pub fn query_league(deps: Deps) -> StdResult<LeagueResponse> {
+    let league = LEAGUE.load(deps.storage)?;
+    let next_season = LEAGUE_SEASONS.load(deps.storage, U64Key::new(league.next_season))?;
+    Ok(LeagueResponse {
+        league,
+        next_season,
+    })
+}
+
+pub fn query_standings(deps: Deps, season_id: u64) -> StdResult<StandingsResponse> {
+    let season = LEAGUE_SEASONS.load(deps.storage, U64Key::new(season_id))?;
+    let mut standings = STANDINGS
+        .prefix(U64Key::new(season_id))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, standing)| standing))
+        .collect::<StdResult<Vec<_>>>()?;
+    sort_standings(&mut standings);
+
+    Ok(StandingsResponse { season, standings })
+}
+
+pub fn query_jackpot(deps: Deps) -> StdResult<JackpotResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let jackpot = JACKPOT.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(JackpotResponse {
+        jackpot,
+        jackpot_bps: config.jackpot_bps,
+        jackpot_odds: config.jackpot_odds,
+    })
+}
+
+pub fn query_house(deps: Deps, denom: String) -> StdResult<HouseResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let house = HOUSE.may_load(deps.storage, &denom)?.unwrap_or_default();
+
+    Ok(HouseResponse {
+        max_exposure: max_exposure(&house, config.house_exposure_bps),
+        exposure_bps: config.house_exposure_bps,
+        max_stake_bps: config.house_max_stake_bps,
+        edge_bps: config.house_edge_bps,
+        house,
+    })
+}
+
+pub fn query_session_key(deps: Deps, key: String) -> StdResult<SessionKeyResponse> {
+    let key = deps.api.addr_validate(&key)?;
+    Ok(SessionKeyResponse {
+        session: SESSION_KEYS.may_load(deps.storage, key)?,
+    })
+}
+
+pub fn query_move_key(deps: Deps, address: String) -> StdResult<MoveKeyResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(MoveKeyResponse {
+        pubkey: MOVE_KEYS.may_load(deps.storage, address)?,
+    })
+}
+
+pub fn query_house_shares(
+    deps: Deps,
+    denom: String,
+    address: String,
+) -> StdResult<HouseSharesResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let house = HOUSE.may_load(deps.storage, &denom)?.unwrap_or_default();
+    let shares = HOUSE_SHARES
+        .may_load(deps.storage, (&denom, address))?
+        .unwrap_or_default();
+
+    Ok(HouseSharesResponse {
+        shares,
+        value: share_value(&house, shares),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_querier::{mock_dependencies, WasmMockQuerier};
+    use crate::msg::MoveSignature;
+    use crate::settlement::MOVE_TIMEOUT;
+    use crate::ultimate;
+    use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{attr, coin, coins, from_binary, Event, OwnedDeps};
+    use cw_controllers::AdminResponse;
+
+    type MockDeps = OwnedDeps<MockStorage, MockApi, WasmMockQuerier>;
+
+    /// Instantiates the contract and starts game 1 between "anyone" and "anyone_else".
+    fn setup_game_in_progress() -> MockDeps {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        let opponent = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+
+        deps
+    }
+
+    fn create_game_msg() -> ExecuteMsg {
+        ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: None,
+            rule_set: RuleSet::Classic,
+            seats: None,
+            vs_house: false,
+        }
+    }
+
+    fn play(deps: &mut MockDeps, player: &str, x: u8, y: u8) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::MakeMove { game_id: 1, x, y };
+        execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg)
+    }
+
+    fn load_game(deps: &MockDeps, id: u64) -> Game {
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id }).unwrap();
+        let value: GameResponse = from_binary(&res).unwrap();
+        value.game
+    }
+
+    #[test]
+    fn init_contract() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::IsLocked {}).unwrap();
+        let value: LockedResponse = from_binary(&res).unwrap();
+        assert_eq!(false, value.locked);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAdmin {}).unwrap();
+        let value: AdminResponse = from_binary(&res).unwrap();
+        assert_eq!("creator", value.admin.unwrap());
+    }
+
+    #[test]
+    fn create_game() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let unauth_info = mock_info("anyone", &coins(100_000_000, "uust"));
+        let msg = create_game_msg();
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
+        let value: GameResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.game.game_id);
+        assert_eq!(Uint128::from(98_000_000u128), value.game.bet.amount);
+    }
+
+    #[test]
+    fn query_games_by_address() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let anyone = mock_info("anyone", &coins(100_000_000, "uust"));
+        for _ in 0..5 {
+            let msg = create_game_msg();
+            let _res = execute(deps.as_mut(), mock_env(), anyone.clone(), msg).unwrap();
+        }
+
+        // All games
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetGames { rule_set: None },
+        )
+        .unwrap();
+        let value: GamesResponse = from_binary(&res).unwrap();
+        assert_eq!(5, value.games.len());
+
+        // Games by address
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetGamesByAddress {
+                address: "anyone".to_string(),
+                rule_set: None,
+            },
+        )
+        .unwrap();
+        let value: GamesResponse = from_binary(&res).unwrap();
+        assert_eq!(5, value.games.len());
+    }
+
+    #[test]
+    fn join_game() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let unauth_info = mock_info("anyone", &coins(100_000_000, "uust"));
+        let msg = create_game_msg();
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        let unauth_info = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
+        let value: GameResponse = from_binary(&res).unwrap();
+        assert_eq!(GameState::InProgress, value.game.state);
+        assert_eq!("anyone_else", value.game.opponent.to_string());
+        assert_eq!("anyone", value.game.host.to_string());
+        assert_eq!(
+            Coin {
+                amount: Uint128::new(196_000_000),
+                denom: "uust".to_string()
+            },
+            value.game.bet
+        );
+    }
+
+    #[test]
+    fn games_need_a_stake() {
+        let mut deps = setup_game_in_progress();
+        let msg = create_game_msg();
+        match execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg) {
+            Err(ContractError::BetDenomInvalid {}) => {}
+            res => panic!("Must return BetDenomInvalid error, got {:?}", res),
+        }
+
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 2,
+            referrer: None,
+        };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        ) {
+            Err(ContractError::BetDenomInvalid {}) => {}
+            res => panic!("Must return BetDenomInvalid error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn new_game_can_be_cancelled() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let unauth_info = mock_info("anyone", &coins(100_000_000, "uust"));
+        let msg = create_game_msg();
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        let unauth_info = mock_info("anyone", &coins(2, "token"));
+        let msg = ExecuteMsg::CancelGame { game_id: 1 };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
+        let value: GameResponse = from_binary(&res).unwrap();
+        assert_eq!(GameState::Cancelled, value.game.state);
+
+        // match res {
+        //     Err(ContractError::Unauthorized {}) => {}
+        //     _ => panic!("Must return unauthorized error"),
+        // }
+
+        // // only the original creator can reset the counter
+        // let auth_info = mock_info("creator", &coins(2, "token"));
+        // let msg = ExecuteMsg::Reset { count: 5 };
+        // let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // // should now be 5
+        // let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
+        // let value: CountResponse = from_binary(&res).unwrap();
+        // assert_eq!(5, value.count);
+    }
+
+    #[test]
+    fn full_game_until_host_wins() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+        deps.querier.with_terrand();
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let unauth_info = mock_info("anyone", &coins(100_000_000, "uust"));
+        let msg = create_game_msg();
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        let unauth_info = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        // X to [0,0]
+        let unauth_info = mock_info("anyone", &coins(100, "uust"));
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 1,
+            x: 0,
+            y: 0,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        // O to [0,1]
+        let unauth_info = mock_info("anyone_else", &coins(100, "uust"));
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 1,
+            x: 0,
+            y: 1,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        // X to [1,0]
+        let unauth_info = mock_info("anyone", &coins(100, "uust"));
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 1,
+            x: 1,
+            y: 0,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        // O to [1,1]
+        let unauth_info = mock_info("anyone_else", &coins(100, "uust"));
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 1,
+            x: 1,
+            y: 1,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        // X to [2,0]
+        let unauth_info = mock_info("anyone", &coins(100, "uust"));
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 1,
+            x: 2,
+            y: 0,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        // O to [3,3]
+        let unauth_info = mock_info("anyone_else", &coins(100, "uust"));
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 1,
+            x: 3,
+            y: 3,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        // X to [3,0]
+        let unauth_info = mock_info("anyone", &coins(100, "uust"));
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 1,
+            x: 3,
+            y: 0,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
+        let value: GameResponse = from_binary(&res).unwrap();
+        assert_eq!(GameState::Completed, value.game.state);
+        assert_eq!("anyone", value.game.winner.to_string());
+
+        // Check leaderboard
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetLeaderboard {}).unwrap();
+        let value: LeaderboardResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries[0].win_count);
+        assert_eq!("anyone", value.entries[0].player);
+    }
+
+    #[test]
+    fn moves_are_recorded_and_paginated() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        let opponent = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+
+        let moves = [("anyone", 0, 0), ("anyone_else", 0, 1), ("anyone", 1, 0)];
+        for &(player, x, y) in moves.iter() {
+            let msg = ExecuteMsg::MakeMove { game_id: 1, x, y };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+        }
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetMoves {
+                game_id: 1,
+                start_after: None,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+        let value: MovesResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.moves.len());
+        assert_eq!(1, value.moves[0].move_no);
+        assert_eq!("anyone", value.moves[0].player);
+        assert_eq!((0, 1), (value.moves[1].x, value.moves[1].y));
+        assert_eq!(mock_env().block.height, value.moves[1].height);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetMoves {
+                game_id: 1,
+                start_after: Some(2),
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: MovesResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.moves.len());
+        assert_eq!(3, value.moves[0].move_no);
+        assert_eq!("anyone", value.moves[0].player);
+        assert_eq!((1, 0), (value.moves[0].x, value.moves[0].y));
+    }
+
+    #[test]
+    fn events_follow_schema() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig {
+            dimension: 6,
+            threshold: 4,
+            fee_bps: 200,
+            referral_percentage: 0,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            vec![Event::new("config_updated")
+                .add_attribute("dimension", "6")
+                .add_attribute("threshold", "4")
+                .add_attribute("fee_bps", "200")
+                .add_attribute("max_fee_bps", "1000")
+                .add_attribute("referral_percentage", "0")
+                .add_attribute("jackpot_bps", "0")
+                .add_attribute("jackpot_odds", "0")
+                .add_attribute("bets_close_after", "4")
+                .add_attribute("house_exposure_bps", "0")
+                .add_attribute("house_max_stake_bps", "0")
+                .add_attribute("house_edge_bps", "0")],
+            res.events
+        );
+
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        assert_eq!(
+            vec![Event::new("game_created")
+                .add_attribute("game_id", "1")
+                .add_attribute("host", "anyone")
+                .add_attribute("bet", "98000000uust")
+                .add_attribute("rule_set", "classic")],
+            res.events
+        );
+
+        let opponent = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+        assert_eq!(
+            vec![Event::new("game_joined")
+                .add_attribute("game_id", "1")
+                .add_attribute("opponent", "anyone_else")
+                .add_attribute("bet", "196000000uust")],
+            res.events
+        );
+
+        let moves = [
+            ("anyone", 0, 0),
+            ("anyone_else", 0, 1),
+            ("anyone", 1, 0),
+            ("anyone_else", 1, 1),
+            ("anyone", 2, 0),
+            ("anyone_else", 3, 3),
+        ];
+        for (move_no, (player, x, y)) in moves.iter().enumerate() {
+            let msg = ExecuteMsg::MakeMove {
+                game_id: 1,
+                x: *x,
+                y: *y,
+            };
+            let res = execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+            assert_eq!(
+                vec![Event::new("move_made")
+                    .add_attribute("game_id", "1")
+                    .add_attribute("player", *player)
+                    .add_attribute("move_no", (move_no + 1).to_string())
+                    .add_attribute("x", x.to_string())
+                    .add_attribute("y", y.to_string())],
+                res.events
+            );
+        }
+
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 1,
+            x: 3,
+            y: 0,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            vec![
+                Event::new("move_made")
+                    .add_attribute("game_id", "1")
+                    .add_attribute("player", "anyone")
+                    .add_attribute("move_no", "7")
+                    .add_attribute("x", "3")
+                    .add_attribute("y", "0"),
+                Event::new("game_won")
+                    .add_attribute("game_id", "1")
+                    .add_attribute("winner", "anyone"),
+            ],
+            res.events
+        );
+
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            vec![Event::new("prize_paid")
+                .add_attribute("game_id", "1")
+                .add_attribute("recipient", "anyone")
+                .add_attribute("amount", "196000000uust")],
+            res.events
+        );
+
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        let msg = ExecuteMsg::CancelGame { game_id: 2 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            vec![
+                Event::new("stake_refunded")
+                    .add_attribute("game_id", "2")
+                    .add_attribute("recipient", "anyone")
+                    .add_attribute("amount", "98000000uust"),
+                Event::new("game_cancelled")
+                    .add_attribute("game_id", "2")
+                    .add_attribute("host", "anyone"),
+            ],
+            res.events
+        );
+    }
+
+    #[test]
+    fn resign_completes_game_for_opponent() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+
+        // Nothing to resign before the opponent joined
+        let msg = ExecuteMsg::Resign { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::NotAllowedInCurrentState { .. }) => {}
+            _ => panic!("Must return NotAllowedInCurrentState error"),
+        }
+
+        let opponent = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+
+        // Spectators cannot resign on behalf of a player
+        let msg = ExecuteMsg::Resign { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("spectator", &[]), msg);
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let msg = ExecuteMsg::Resign { game_id: 1 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
+        let value: GameResponse = from_binary(&res).unwrap();
+        assert_eq!(GameState::Completed, value.game.state);
+        assert_eq!("anyone_else", value.game.winner.to_string());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetLeaderboard {}).unwrap();
+        let value: LeaderboardResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.entries[0].win_count);
+        assert_eq!("anyone_else", value.entries[0].player);
+
+        // The winner does not have to wait for the timeout
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "anyone_else".to_string(),
+                amount: coins(196_000_000, "uust"),
+            })],
+            res.messages
+        );
+    }
+
+    #[test]
+    fn accepted_draw_refunds_both_stakes() {
+        let mut deps = setup_game_in_progress();
+        play(&mut deps, "anyone", 0, 0).unwrap();
+
+        // Nothing to accept yet
+        let msg = ExecuteMsg::AcceptDraw { game_id: 1 };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        );
+        match res {
+            Err(ContractError::NoPendingOffer {}) => {}
+            _ => panic!("Must return NoPendingOffer error"),
+        }
+
+        let msg = ExecuteMsg::OfferDraw { game_id: 1 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            Some(Offer {
+                kind: OfferKind::Draw,
+                from: Addr::unchecked("anyone"),
+            }),
+            load_game(&deps, 1).pending_offer
+        );
+
+        // A second offer cannot replace the pending one
+        let msg = ExecuteMsg::OfferDraw { game_id: 1 };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        );
+        match res {
+            Err(ContractError::OfferAlreadyPending {}) => {}
+            _ => panic!("Must return OfferAlreadyPending error"),
+        }
+
+        // The player who made the offer cannot accept it
+        let msg = ExecuteMsg::AcceptDraw { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::NoPendingOffer {}) => {}
+            _ => panic!("Must return NoPendingOffer error"),
+        }
+
+        let msg = ExecuteMsg::AcceptDraw { game_id: 1 };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "anyone".to_string(),
+                    amount: coins(98_000_000, "uust"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "anyone_else".to_string(),
+                    amount: coins(98_000_000, "uust"),
+                }),
+            ],
+            res.messages
+        );
+
+        let game = load_game(&deps, 1);
+        assert_eq!(GameState::Drawn, game.state);
+        assert_eq!(None, game.pending_offer);
+
+        // A drawn game pays out nothing more
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::PriceCannotBeWithdrawn {}) => {}
+            _ => panic!("Must return PriceCannotBeWithdrawn error"),
+        }
+    }
+
+    #[test]
+    fn draw_offer_can_be_declined_or_expires() {
+        let mut deps = setup_game_in_progress();
+
+        let msg = ExecuteMsg::OfferDraw { game_id: 1 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        let msg = ExecuteMsg::DeclineDraw { game_id: 1 };
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(None, load_game(&deps, 1).pending_offer);
+
+        let msg = ExecuteMsg::OfferDraw { game_id: 1 };
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        )
+        .unwrap();
+        play(&mut deps, "anyone", 0, 0).unwrap();
+        assert_eq!(None, load_game(&deps, 1).pending_offer);
+
+        let msg = ExecuteMsg::AcceptDraw { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::NoPendingOffer {}) => {}
+            _ => panic!("Must return NoPendingOffer error"),
+        }
+        assert_eq!(GameState::InProgress, load_game(&deps, 1).state);
+    }
+
+    #[test]
+    fn accepted_takeback_undoes_last_move() {
+        let mut deps = setup_game_in_progress();
+
+        // Nothing to take back before the first move
+        let msg = ExecuteMsg::RequestTakeback { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::TakebackNotAllowed {}) => {}
+            _ => panic!("Must return TakebackNotAllowed error"),
+        }
+
+        play(&mut deps, "anyone", 0, 0).unwrap();
+        play(&mut deps, "anyone_else", 0, 1).unwrap();
+
+        // Only the last mover may ask for a takeback
+        let msg = ExecuteMsg::RequestTakeback { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::TakebackNotAllowed {}) => {}
+            _ => panic!("Must return TakebackNotAllowed error"),
+        }
+
+        let msg = ExecuteMsg::RequestTakeback { game_id: 1 };
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::AcceptTakeback { game_id: 1 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+
+        let game = load_game(&deps, 1);
+        assert_eq!(0, game.grid[0][1]);
+        assert_eq!(1, game.move_count);
+        assert_eq!("anyone_else", game.next_player);
+        assert_eq!(None, game.pending_offer);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetMoves {
+                game_id: 1,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: MovesResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.moves.len());
+
+        // The taken back move is replaced by a fresh one
+        play(&mut deps, "anyone_else", 1, 1).unwrap();
+        let game = load_game(&deps, 1);
+        assert_eq!(100, game.grid[1][1]);
+        assert_eq!(2, game.move_count);
+    }
+
+    #[test]
+    fn winning_move_pays_out_with_auto_payout() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: true,
+            referrer: None,
+            rule_set: RuleSet::Classic,
+            seats: None,
+            vs_house: false,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), host, msg).unwrap();
+        let opponent = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+
+        play(&mut deps, "anyone", 0, 0).unwrap();
+        play(&mut deps, "anyone_else", 0, 1).unwrap();
+        play(&mut deps, "anyone", 1, 0).unwrap();
+        play(&mut deps, "anyone_else", 1, 1).unwrap();
+        play(&mut deps, "anyone", 2, 0).unwrap();
+        let res = play(&mut deps, "anyone_else", 3, 3).unwrap();
+        assert!(res.messages.is_empty());
+
+        let res = play(&mut deps, "anyone", 3, 0).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "anyone".to_string(),
+                amount: coins(196_000_000, "uust"),
+            })],
+            res.messages
+        );
+
+        let game = load_game(&deps, 1);
+        assert_eq!(GameState::PriceWithdrawn, game.state);
+        assert_eq!("anyone", game.winner);
+
+        // Nothing is left to claim
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::PriceCannotBeWithdrawn {}) => {}
+            _ => panic!("Must return PriceCannotBeWithdrawn error"),
+        }
+    }
+
+    #[test]
+    fn claim_all_settles_every_won_game_at_once() {
+        let mut deps = setup_game_in_progress();
+
+        // Games 2 and 3 are won by "anyone" as well, game 4 is lost
+        for _ in 0..3 {
+            let host = mock_info("anyone", &coins(50_000_000, "uust"));
+            let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        }
+        for game_id in 2..5 {
+            let opponent = mock_info("anyone_else", &coins(50_000_000, "uust"));
+            let msg = ExecuteMsg::JoinGame {
+                game_id,
+                referrer: None,
+            };
+            let _res = execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+        }
+        for game_id in 1..4 {
+            let msg = ExecuteMsg::Resign { game_id };
+            let _res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("anyone_else", &[]),
+                msg,
+            )
+            .unwrap();
+        }
+        let msg = ExecuteMsg::Resign { game_id: 4 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+
+        // Game 1 was already claimed on its own
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::ClaimAll { limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "anyone".to_string(),
+                amount: coins(196_000_000, "uust"),
+            })],
+            res.messages
+        );
+        assert!(res.attributes.contains(&attr("game_ids", "2,3")));
+        assert_eq!(GameState::PriceWithdrawn, load_game(&deps, 2).state);
+        assert_eq!(GameState::PriceWithdrawn, load_game(&deps, 3).state);
+        assert_eq!(GameState::Completed, load_game(&deps, 4).state);
+
+        let msg = ExecuteMsg::ClaimAll { limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::PriceCannotBeWithdrawn {}) => {}
+            _ => panic!("Must return PriceCannotBeWithdrawn error"),
+        }
+
+        let msg = ExecuteMsg::ClaimAll { limit: Some(1) };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        )
+        .unwrap();
+        assert!(res.attributes.contains(&attr("game_ids", "4")));
+    }
+
+    #[test]
+    fn claim_all_skips_teammates_left_with_nothing() {
+        let mut deps = setup_game_in_progress();
+        let game = Game {
+            game_id: 2,
+            host: Addr::unchecked("anyone"),
+            opponent: Addr::unchecked("anyone_else"),
+            players: vec![Addr::unchecked("anyone"), Addr::unchecked("anyone_else")],
+            teams: vec![
+                vec![Addr::unchecked("mate"), Addr::unchecked("other_mate")],
+                vec![],
+            ],
+            bet: coin(2, "uust"),
+            state: GameState::Completed,
+            winner: Addr::unchecked("anyone"),
+            ..crate::testing::mock_game()
+        };
+        games()
+            .save(deps.as_mut().storage, U64Key::new(2), &game)
+            .unwrap();
+
+        // Splitting the pot of 2 three ways leaves both teammates without a share
+        let msg = ExecuteMsg::ClaimAll { limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "anyone".to_string(),
+                amount: coins(2, "uust"),
+            })],
+            res.messages
+        );
+        assert_eq!(
+            vec![Event::new("prize_paid")
+                .add_attribute("game_id", "2")
+                .add_attribute("recipient", "anyone")
+                .add_attribute("amount", "2uust")],
+            res.events
+        );
+        assert_eq!(GameState::PriceWithdrawn, load_game(&deps, 2).state);
+    }
+
+    #[test]
+    fn withdraw_price_only_pays_the_rightful_recipient() {
+        let mut deps = setup_game_in_progress();
+        play(&mut deps, "anyone", 0, 0).unwrap();
+
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(MOVE_TIMEOUT + 1);
+
+        // The stalled player and strangers cannot take the pot
+        for &caller in ["anyone_else", "stranger"].iter() {
+            let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+            let res = execute(deps.as_mut(), later.clone(), mock_info(caller, &[]), msg);
+            match res {
+                Err(ContractError::Unauthorized {}) => {}
+                _ => panic!("Must return unauthorized error"),
+            }
+        }
+
+        // The waiting player has to wait for the timeout
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::PriceCannotBeWithdrawn {}) => {}
+            _ => panic!("Must return PriceCannotBeWithdrawn error"),
+        }
+
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        let res = execute(deps.as_mut(), later.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "anyone".to_string(),
+                amount: coins(196_000_000, "uust"),
+            })],
+            res.messages
+        );
+        let game = load_game(&deps, 1);
+        assert_eq!(GameState::PriceWithdrawn, game.state);
+        assert_eq!("anyone", game.winner);
+
+        // No second payout
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        let res = execute(deps.as_mut(), later, mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::PriceCannotBeWithdrawn {}) => {}
+            _ => panic!("Must return PriceCannotBeWithdrawn error"),
+        }
+    }
+
+    #[test]
+    fn cancelled_game_refunds_host_stake() {
+        let mut deps = setup_game_in_progress();
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+
+        let msg = ExecuteMsg::CancelGame { game_id: 2 };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        );
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let msg = ExecuteMsg::CancelGame { game_id: 2 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "anyone".to_string(),
+                amount: coins(98_000_000, "uust"),
+            })],
+            res.messages
+        );
+
+        let msg = ExecuteMsg::CancelGame { game_id: 2 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::GameCannotBeCancelled {}) => {}
+            _ => panic!("Must return GameCannotBeCancelled error"),
+        }
+    }
+
+    #[test]
+    fn referrers_earn_a_share_of_fees() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig {
+            dimension: 6,
+            threshold: 4,
+            fee_bps: 200,
+            referral_percentage: 101,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg);
+        match res {
+            Err(ContractError::InvalidReferralPercentage {}) => {}
+            _ => panic!("Must return InvalidReferralPercentage error"),
+        }
+        let msg = ExecuteMsg::UpdateConfig {
+            dimension: 6,
+            threshold: 4,
+            fee_bps: 200,
+            referral_percentage: 50,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // Players cannot refer themselves
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: Some("anyone".to_string()),
+            rule_set: RuleSet::Classic,
+            seats: None,
+            vs_house: false,
+        };
+        let res = execute(deps.as_mut(), mock_env(), host.clone(), msg);
+        match res {
+            Err(ContractError::InvalidReferrer {}) => {}
+            _ => panic!("Must return InvalidReferrer error"),
+        }
+
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: Some("promoter".to_string()),
+            rule_set: RuleSet::Classic,
+            seats: None,
+            vs_house: false,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), host.clone(), msg).unwrap();
+        let opponent = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: Some("promoter".to_string()),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+
+        // The first referrer sticks, the fee share keeps flowing to it
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: Some("someone_else".to_string()),
+            rule_set: RuleSet::Classic,
+            seats: None,
+            vs_house: false,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), host, msg).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetReferralStats {
+                address: "promoter".to_string(),
+            },
+        )
+        .unwrap();
+        let value: ReferralStatsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.referred_players);
+        assert_eq!(coins(3_000_000, "uust"), value.pending_rewards);
+        assert_eq!(coins(3_000_000, "uust"), value.total_earned);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetReferralStats {
+                address: "anyone".to_string(),
+            },
+        )
+        .unwrap();
+        let value: ReferralStatsResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(Addr::unchecked("promoter")), value.referrer);
+        assert_eq!(0, value.referred_players);
+
+        let msg = ExecuteMsg::ClaimReferralRewards {};
+        let res = execute(deps.as_mut(), mock_env(), mock_info("promoter", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "promoter".to_string(),
+                amount: coins(3_000_000, "uust"),
+            })],
+            res.messages
+        );
+
+        let msg = ExecuteMsg::ClaimReferralRewards {};
+        let res = execute(deps.as_mut(), mock_env(), mock_info("promoter", &[]), msg);
+        match res {
+            Err(ContractError::NoReferralRewards {}) => {}
+            _ => panic!("Must return NoReferralRewards error"),
+        }
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetReferralStats {
+                address: "promoter".to_string(),
+            },
+        )
+        .unwrap();
+        let value: ReferralStatsResponse = from_binary(&res).unwrap();
+        assert!(value.pending_rewards.is_empty());
+        assert_eq!(coins(3_000_000, "uust"), value.total_earned);
+    }
+
+    #[test]
+    fn fees_are_distributed_to_beneficiaries() {
+        let mut deps = setup_game_in_progress();
+
+        let shares = vec![
+            FeeShareMsg {
+                name: "treasury".to_string(),
+                address: "treasury".to_string(),
+                weight: 6_000,
+            },
+            FeeShareMsg {
+                name: "dev_fund".to_string(),
+                address: "dev_fund".to_string(),
+                weight: 3_000,
+            },
+            FeeShareMsg {
+                name: "burn".to_string(),
+                address: "burn".to_string(),
+                weight: 999,
+            },
+        ];
+
+        // Nothing can be distributed before a table is configured
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::DistributeFees {},
+        );
+        match res {
+            Err(ContractError::InvalidFeeDistribution {}) => {}
+            _ => panic!("Must return InvalidFeeDistribution error"),
+        }
+
+        let msg = ExecuteMsg::UpdateFeeDistribution {
+            shares: shares.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        assert!(res.is_err());
+
+        // Weights have to add up to 10000
+        let msg = ExecuteMsg::UpdateFeeDistribution {
+            shares: shares.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg);
+        match res {
+            Err(ContractError::InvalidFeeDistribution {}) => {}
+            _ => panic!("Must return InvalidFeeDistribution error"),
+        }
+
+        let mut shares = shares;
+        shares[2].weight = 1_000;
+        let msg = ExecuteMsg::UpdateFeeDistribution { shares };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPendingDistribution {},
+        )
+        .unwrap();
+        let value: PendingDistributionResponse = from_binary(&res).unwrap();
+        assert_eq!(coins(4_000_000, "uust"), value.fee_pool);
+        assert_eq!(
+            vec![
+                coins(2_400_000, "uust"),
+                coins(1_200_000, "uust"),
+                coins(400_000, "uust")
+            ],
+            value
+                .shares
+                .into_iter()
+                .map(|share| share.amount)
+                .collect::<Vec<Vec<Coin>>>()
+        );
+
+        // Anyone can trigger the distribution
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::DistributeFees {},
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "treasury".to_string(),
+                    amount: coins(2_400_000, "uust"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "dev_fund".to_string(),
+                    amount: coins(1_200_000, "uust"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "burn".to_string(),
+                    amount: coins(400_000, "uust"),
+                }),
+            ],
+            res.messages
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::DistributeFees {},
+        );
+        match res {
+            Err(ContractError::NoFeesToDistribute {}) => {}
+            _ => panic!("Must return NoFeesToDistribute error"),
+        }
+    }
+
+    #[test]
+    fn fees_follow_tiers_and_caps() {
+        let mut deps = setup_game_in_progress();
+        // Default fee of 2% on the stakes of host and opponent
+        assert_eq!(Uint128::new(196_000_000), load_game(&deps, 1).bet.amount);
+
+        let admin = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdateFeeLimits {
+            max_fee_bps: 100,
+            fee_tiers: vec![],
+        };
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), msg);
+        match res {
+            Err(ContractError::InvalidFeeBps {}) => {}
+            _ => panic!("Must return InvalidFeeBps error"),
+        }
+
+        let msg = ExecuteMsg::UpdateFeeLimits {
+            max_fee_bps: 500,
+            fee_tiers: vec![FeeTier {
+                min_stake: Uint128::new(50_000_000),
+                fee_bps: 100,
+            }],
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateFeeCaps {
+            denom: "uust".to_string(),
+            min_fee: Some(Uint128::new(500_000)),
+            max_fee: Some(Uint128::new(100_000)),
+        };
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), msg);
+        match res {
+            Err(ContractError::InvalidFeeCaps {}) => {}
+            _ => panic!("Must return InvalidFeeCaps error"),
+        }
+
+        let msg = ExecuteMsg::UpdateFeeCaps {
+            denom: "uust".to_string(),
+            min_fee: Some(Uint128::new(500_000)),
+            max_fee: Some(Uint128::new(1_500_000)),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+
+        // Small stakes pay the base fee of 2% but at least the minimum fee
+        let host = mock_info("anyone", &coins(20_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        assert_eq!(Uint128::new(19_500_000), load_game(&deps, 2).bet.amount);
+
+        // Large stakes pay the tier fee of 1% but at most the maximum fee
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        assert_eq!(Uint128::new(99_000_000), load_game(&deps, 3).bet.amount);
+        let host = mock_info("anyone", &coins(200_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        assert_eq!(Uint128::new(198_500_000), load_game(&deps, 4).bet.amount);
+
+        // A minimum fee taking the whole stake would leave an empty pot
+        let msg = ExecuteMsg::UpdateFeeCaps {
+            denom: "uust".to_string(),
+            min_fee: Some(Uint128::new(20_000_000)),
+            max_fee: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let host = mock_info("anyone", &coins(20_000_000, "uust"));
+        match execute(deps.as_mut(), mock_env(), host, create_game_msg()) {
+            Err(ContractError::StakeBelowFee {}) => {}
+            _ => panic!("Must return StakeBelowFee error"),
+        }
+    }
+
+    #[test]
+    fn lucky_draw_pays_out_the_jackpot() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            min_bet: Coin {
+                amount: Uint128::new(10_000_000),
+                denom: "uust".to_string(),
+            },
+            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateJackpot {
+            jackpot_bps: 10_001,
+            jackpot_odds: 1,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg);
+        match res {
+            Err(ContractError::InvalidFeeBps {}) => {}
+            _ => panic!("Must return InvalidFeeBps error"),
+        }
+        // Every draw hits at odds of one in one
+        let msg = ExecuteMsg::UpdateJackpot {
+            jackpot_bps: 5_000,
+            jackpot_odds: 1,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let host = mock_info("anyone", &coins(100_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), host, create_game_msg()).unwrap();
+        let opponent = mock_info("anyone_else", &coins(100_000_000, "uust"));
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 1,
+            referrer: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+
+        // Half of both fees went into the jackpot, the other half into the fee pool
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetJackpot {}).unwrap();
+        let value: JackpotResponse = from_binary(&res).unwrap();
+        assert_eq!(coins(2_000_000, "uust"), value.jackpot);
+        assert_eq!(5_000, value.jackpot_bps);
+        assert_eq!(1, value.jackpot_odds);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetPendingDistribution {},
+        )
+        .unwrap();
+        let value: PendingDistributionResponse = from_binary(&res).unwrap();
+        assert_eq!(coins(2_000_000, "uust"), value.fee_pool);
+
+        play(&mut deps, "anyone", 0, 0).unwrap();
+        play(&mut deps, "anyone_else", 0, 1).unwrap();
+        play(&mut deps, "anyone", 1, 0).unwrap();
+        play(&mut deps, "anyone_else", 1, 1).unwrap();
+        play(&mut deps, "anyone", 2, 0).unwrap();
+        play(&mut deps, "anyone_else", 3, 3).unwrap();
+        // The win only schedules a draw on a round that is not out yet
+        let res = play(&mut deps, "anyone", 3, 0).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(
+            Event::new("jackpot_draw_scheduled")
+                .add_attribute("game_id", "1")
+                .add_attribute("winner", "anyone")
+                .add_attribute("round", "1883858"),
+            res.events[2]
+        );
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetJackpotDraw { game_id: 1 },
+        )
+        .unwrap();
+        let value: JackpotDrawResponse = from_binary(&res).unwrap();
+        assert_eq!(1883858, value.draw.unwrap().round);
+
+        let msg = ExecuteMsg::DrawJackpot { game_id: 1 };
+        match execute(deps.as_mut(), mock_env(), mock_info("stranger", &[]), msg) {
+            Err(ContractError::JackpotDrawPending {}) => {}
+            _ => panic!("Must return JackpotDrawPending error"),
+        }
+
+        deps.querier.with_latest_round(1883858);
+        let msg = ExecuteMsg::DrawJackpot { game_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("stranger", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "anyone".to_string(),
+                amount: coins(2_000_000, "uust"),
+            })],
+            res.messages
+        );
+        assert_eq!(
+            vec![
+                Event::new("jackpot_drawn")
+                    .add_attribute("game_id", "1")
+                    .add_attribute("round", "1883858")
+                    .add_attribute("hit", "true"),
+                Event::new("jackpot_won")
+                    .add_attribute("game_id", "1")
+                    .add_attribute("winner", "anyone")
+                    .add_attribute("amount", "2000000uust")
+            ],
+            res.events
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetJackpot {}).unwrap();
+        let value: JackpotResponse = from_binary(&res).unwrap();
+        assert!(value.jackpot.is_empty());
+        let msg = ExecuteMsg::DrawJackpot { game_id: 1 };
+        match execute(deps.as_mut(), mock_env(), mock_info("stranger", &[]), msg) {
+            Err(ContractError::NoJackpotDraw {}) => {}
+            _ => panic!("Must return NoJackpotDraw error"),
+        }
+    }
 
-    Ok(GamesResponse { games: games })
-}
+    #[test]
+    fn tournament_runs_from_registration_to_payout() {
+        let mut deps = setup_game_in_progress();
+        let players = ["alice", "bob", "carol", "dave"];
+        let entry_fee = coins(10_000_000, "uust");
+        let rules = BoardRules {
+            dimension: 6,
+            threshold: 4,
+        };
 
-pub fn query_leaderboard(deps: Deps) -> StdResult<LeaderboardResponse> {
-    let leaderboard_entries = LEADERBOARD
-        .range(deps.storage, None, None, Order::Descending)
-        .map(|item| {
-            let (k, win_count) = item.unwrap();
-            let address = match std::str::from_utf8(&k) {
-                Ok(v) => v,
-                Err(e) => panic!("Invalid UTF-8 sequence: {}", e),
-            };
-            let player = Addr::unchecked(address);
-            LeaderBoardEntry {
-                player: player,
-                win_count,
-            }
-        })
-        .collect();
+        let msg = ExecuteMsg::CreateTournament {
+            entry_fee: entry_fee[0].clone(),
+            bracket_size: 3,
+            rules,
+            prize_distribution: vec![10_000],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg);
+        match res {
+            Err(ContractError::InvalidBracketSize {}) => {}
+            _ => panic!("Must return InvalidBracketSize error"),
+        }
+        let msg = ExecuteMsg::CreateTournament {
+            entry_fee: entry_fee[0].clone(),
+            bracket_size: 4,
+            rules,
+            prize_distribution: vec![7_000, 2_000],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg);
+        match res {
+            Err(ContractError::InvalidPrizeDistribution {}) => {}
+            _ => panic!("Must return InvalidPrizeDistribution error"),
+        }
+        let msg = ExecuteMsg::CreateTournament {
+            entry_fee: entry_fee[0].clone(),
+            bracket_size: 4,
+            rules,
+            prize_distribution: vec![7_000, 3_000],
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
 
-    Ok(LeaderboardResponse {
-        entries: leaderboard_entries,
-    })
-}
+        let register = ExecuteMsg::RegisterForTournament { tournament_id: 1 };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &coins(5_000_000, "uust")),
+            register.clone(),
+        );
+        match res {
+            Err(ContractError::EntryFeeInvalid {}) => {}
+            _ => panic!("Must return EntryFeeInvalid error"),
+        }
+        for player in players.iter() {
+            let info = mock_info(player, &entry_fee);
+            let _res = execute(deps.as_mut(), mock_env(), info, register.clone()).unwrap();
+        }
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("erin", &entry_fee),
+            register,
+        );
+        match res {
+            Err(ContractError::RegistrationClosed {}) => {}
+            _ => panic!("Must return RegistrationClosed error"),
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::mock_querier::mock_dependencies;
-    use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{coins, from_binary, Uint128};
-    use cw_controllers::AdminResponse;
+        // Game 1 is the running game of "anyone", the semi-finals are games 2 and 3
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetTournament { tournament_id: 1 },
+        )
+        .unwrap();
+        let tournament = from_binary::<TournamentResponse>(&res).unwrap().tournament;
+        assert_eq!(TournamentState::InProgress, tournament.state);
+        assert_eq!(vec![2, 3], tournament.games);
+        let semi_final = load_game(&deps, 2);
+        assert_eq!(GameState::InProgress, semi_final.state);
+        assert_eq!(Some(1), semi_final.tournament_id);
+        assert!(semi_final.bet.amount.is_zero());
+        assert_eq!(tournament.bracket[0], semi_final.host);
+        assert_eq!(tournament.bracket[1], semi_final.opponent);
 
-    #[test]
-    fn init_contract() {
-        let mut deps = mock_dependencies(&coins(2, "token"));
+        // The host of the first semi-final resigns, the second one is played out
+        let msg = ExecuteMsg::Resign { game_id: 2 };
+        let info = mock_info(semi_final.host.as_str(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.messages.is_empty());
+        let semi_final = load_game(&deps, 3);
+        let host = semi_final.host.as_str();
+        let opponent = semi_final.opponent.as_str();
+        let moves = [
+            (host, 0, 0),
+            (opponent, 0, 1),
+            (host, 1, 0),
+            (opponent, 1, 1),
+            (host, 2, 0),
+            (opponent, 3, 3),
+            (host, 3, 0),
+        ];
+        for &(player, x, y) in moves.iter() {
+            let msg = ExecuteMsg::MakeMove { game_id: 3, x, y };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+        }
 
-        let msg = InstantiateMsg {
-            min_bet: Coin {
+        let final_game = load_game(&deps, 4);
+        assert_eq!(tournament.bracket[1], final_game.host);
+        assert_eq!(semi_final.host, final_game.opponent);
+        let msg = ExecuteMsg::OfferDraw { game_id: 4 };
+        let info = mock_info(final_game.host.as_str(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::DrawNotAllowed {}) => {}
+            _ => panic!("Must return DrawNotAllowed error"),
+        }
+
+        let msg = ExecuteMsg::Resign { game_id: 4 };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: final_game.opponent.to_string(),
+                    amount: coins(28_000_000, "uust"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: final_game.host.to_string(),
+                    amount: coins(12_000_000, "uust"),
+                }),
+            ],
+            res.messages
+        );
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetTournaments {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let tournaments = from_binary::<TournamentsResponse>(&res)
+            .unwrap()
+            .tournaments;
+        assert_eq!(1, tournaments.len());
+        assert_eq!(TournamentState::Completed, tournaments[0].state);
+        assert_eq!(vec![final_game.opponent], tournaments[0].bracket);
+    }
+
+    #[test]
+    fn tournament_can_be_cancelled_during_registration() {
+        let mut deps = setup_game_in_progress();
+        let msg = ExecuteMsg::CreateTournament {
+            entry_fee: Coin {
                 amount: Uint128::new(10_000_000),
                 denom: "uust".to_string(),
             },
-            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+            bracket_size: 2,
+            rules: BoardRules {
+                dimension: 6,
+                threshold: 4,
+            },
+            prize_distribution: vec![10_000],
         };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RegisterForTournament { tournament_id: 1 };
+        let info = mock_info("bob", &coins(10_000_000, "uust"));
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::IsLocked {}).unwrap();
-        let value: LockedResponse = from_binary(&res).unwrap();
-        assert_eq!(false, value.locked);
+        let msg = ExecuteMsg::CancelTournament { tournament_id: 1 };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return Unauthorized error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "bob".to_string(),
+                amount: coins(10_000_000, "uust"),
+            })],
+            res.messages
+        );
+    }
 
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAdmin {}).unwrap();
-        let value: AdminResponse = from_binary(&res).unwrap();
-        assert_eq!("creator", value.admin.unwrap());
+    #[test]
+    fn league_seasons_roll_over_and_keep_standings() {
+        let mut deps = setup_game_in_progress();
+        let rules = BoardRules {
+            dimension: 6,
+            threshold: 4,
+        };
+        let points = LeaguePoints {
+            win: 3,
+            draw: 1,
+            loss: 0,
+        };
+
+        let msg = ExecuteMsg::JoinLeagueSeason {};
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg);
+        match res {
+            Err(ContractError::LeagueNotConfigured {}) => {}
+            _ => panic!("Must return LeagueNotConfigured error"),
+        }
+        let msg = ExecuteMsg::ConfigureLeague {
+            season_duration: 1_000,
+            rules,
+            points,
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::Admin(_)) => {}
+            _ => panic!("Must return Admin error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        for player in ["alice", "bob", "carol"].iter() {
+            let msg = ExecuteMsg::JoinLeagueSeason {};
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::JoinLeagueSeason {};
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg);
+        match res {
+            Err(ContractError::AlreadyRegistered {}) => {}
+            _ => panic!("Must return AlreadyRegistered error"),
+        }
+
+        // Season 1 starts after a season long registration and schedules a game per pair
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1_000);
+        let msg = ExecuteMsg::AdvanceLeague {};
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetLeague {}).unwrap();
+        let value: LeagueResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.league.next_season);
+        assert_eq!(env.block.time.plus_seconds(1_000), value.next_season.start);
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStandings { season_id: 1 },
+        )
+        .unwrap();
+        let value: StandingsResponse = from_binary(&res).unwrap();
+        assert_eq!(SeasonState::InProgress, value.season.state);
+        assert_eq!(vec![2, 3, 4], value.season.games);
+        let game = load_game(&deps, 2);
+        assert_eq!(Some(1), game.season_id);
+        assert_eq!(GameState::InProgress, game.state);
+
+        // Bob resigns game 2 against alice, alice and carol draw game 3
+        let msg = ExecuteMsg::Resign { game_id: 2 };
+        let info = mock_info(game.host.as_str(), &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert!(res.messages.is_empty());
+        let game = load_game(&deps, 3);
+        let msg = ExecuteMsg::OfferDraw { game_id: 3 };
+        let info = mock_info(game.host.as_str(), &[]);
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = ExecuteMsg::AcceptDraw { game_id: 3 };
+        let info = mock_info(game.opponent.as_str(), &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert!(res.messages.is_empty());
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStandings { season_id: 1 },
+        )
+        .unwrap();
+        let value: StandingsResponse = from_binary(&res).unwrap();
+        let table: Vec<(&str, u32, u32)> = value
+            .standings
+            .iter()
+            .map(|s| (s.player.as_str(), s.played, s.points))
+            .collect();
+        assert_eq!(vec![("alice", 2, 4), ("carol", 1, 1), ("bob", 1, 0)], table);
+
+        // Game 4 is decided after the season ended and does not count any more
+        env.block.time = env.block.time.plus_seconds(1_000);
+        let game = load_game(&deps, 4);
+        let msg = ExecuteMsg::Resign { game_id: 4 };
+        let info = mock_info(game.host.as_str(), &[]);
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GetStandings { season_id: 1 },
+        )
+        .unwrap();
+        let value: StandingsResponse = from_binary(&res).unwrap();
+        assert_eq!(SeasonState::Finished, value.season.state);
+        let played: u32 = value.standings.iter().map(|s| s.played).sum();
+        assert_eq!(4, played);
+        let res = query(deps.as_ref(), env, QueryMsg::GetStandings { season_id: 2 }).unwrap();
+        let value: StandingsResponse = from_binary(&res).unwrap();
+        assert_eq!(SeasonState::InProgress, value.season.state);
+        assert!(value.standings.is_empty());
     }
 
     #[test]
-    fn create_game() {
-        let mut deps = mock_dependencies(&coins(2, "token"));
+    fn leaderboard_seasons_archive_and_reward_the_top() {
+        let mut deps = setup_game_in_progress();
 
-        let msg = InstantiateMsg {
-            min_bet: Coin {
-                amount: Uint128::new(10_000_000),
-                denom: "uust".to_string(),
-            },
-            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        let msg = ExecuteMsg::StartSeason {
+            reward_distribution: vec![7_000, 2_000],
         };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &coins(1_000, "uust"));
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidRewardDistribution {}) => {}
+            _ => panic!("Must return InvalidRewardDistribution error"),
+        }
+        let msg = ExecuteMsg::StartSeason {
+            reward_distribution: vec![7_000, 3_000],
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &coins(1_000, "uust")),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::Admin(_)) => {}
+            _ => panic!("Must return Admin error"),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let unauth_info = mock_info("anyone", &coins(100_000_000, "uust"));
-        let msg = ExecuteMsg::CreateGame {};
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        // "anyone" wins games 1 and 3, "anyone_else" wins game 2
+        for &(game_id, host, loser) in [
+            (1, "anyone", "anyone_else"),
+            (2, "anyone_else", "anyone"),
+            (3, "anyone", "anyone_else"),
+        ]
+        .iter()
+        {
+            if game_id > 1 {
+                let info = mock_info(host, &coins(100_000_000, "uust"));
+                let _res = execute(deps.as_mut(), mock_env(), info, create_game_msg()).unwrap();
+                let joiner = if host == "anyone" {
+                    "anyone_else"
+                } else {
+                    "anyone"
+                };
+                let msg = ExecuteMsg::JoinGame {
+                    game_id,
+                    referrer: None,
+                };
+                let info = mock_info(joiner, &coins(100_000_000, "uust"));
+                let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            }
+            let msg = ExecuteMsg::Resign { game_id };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(loser, &[]), msg).unwrap();
+        }
 
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
-        let value: GameResponse = from_binary(&res).unwrap();
-        assert_eq!(1, value.game.game_id);
-        assert_eq!(Uint128::from(98_000_000u128), value.game.bet.amount);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetSeasonLeaderboard { season_id: None },
+        )
+        .unwrap();
+        let value: LeaderboardResponse = from_binary(&res).unwrap();
+        let entries: Vec<(&str, u64)> = value
+            .entries
+            .iter()
+            .map(|e| (e.player.as_str(), e.win_count))
+            .collect();
+        assert_eq!(vec![("anyone", 2), ("anyone_else", 1)], entries);
+
+        let msg = ExecuteMsg::StartSeason {
+            reward_distribution: vec![],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "anyone".to_string(),
+                    amount: coins(700, "uust"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "anyone_else".to_string(),
+                    amount: coins(300, "uust"),
+                }),
+            ],
+            res.messages
+        );
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetLeaderboardSeason { season_id: 1 },
+        )
+        .unwrap();
+        let season = from_binary::<LeaderboardSeasonResponse>(&res)
+            .unwrap()
+            .season;
+        assert_eq!(Some(mock_env().block.time), season.ended_at);
+        assert_eq!(2, season.results.len());
+        assert_eq!(2, season.results[0].win_count);
+
+        // The new season starts from scratch, the all time leaderboard is kept
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetSeasonLeaderboard { season_id: None },
+        )
+        .unwrap();
+        let value: LeaderboardResponse = from_binary(&res).unwrap();
+        assert!(value.entries.is_empty());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetLeaderboard {}).unwrap();
+        let value: LeaderboardResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.entries.len());
     }
 
     #[test]
-    fn query_games_by_address() {
-        let mut deps = mock_dependencies(&coins(2, "token"));
+    fn spectators_bet_on_games() {
+        let mut deps = setup_game_in_progress();
+        let bet = |deps: &mut MockDeps, bettor: &str, funds: &[Coin], outcome: BetOutcome| {
+            let msg = ExecuteMsg::PlaceBet {
+                game_id: 1,
+                outcome,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(bettor, funds), msg)
+        };
+        let claim = |deps: &mut MockDeps, bettor: &str| {
+            let msg = ExecuteMsg::ClaimBet { game_id: 1 };
+            execute(deps.as_mut(), mock_env(), mock_info(bettor, &[]), msg)
+        };
 
-        let msg = InstantiateMsg {
-            min_bet: Coin {
-                amount: Uint128::new(10_000_000),
-                denom: "uust".to_string(),
+        match bet(&mut deps, "anyone", &coins(100, "uust"), BetOutcome::Host) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!("Must return Unauthorized error, got {:?}", res),
+        }
+        match bet(&mut deps, "alice", &coins(100, "uluna"), BetOutcome::Host) {
+            Err(ContractError::BetDenomInvalid {}) => {}
+            res => panic!("Must return BetDenomInvalid error, got {:?}", res),
+        }
+        bet(&mut deps, "alice", &coins(200, "uust"), BetOutcome::Host).unwrap();
+        bet(&mut deps, "bob", &coins(100, "uust"), BetOutcome::Opponent).unwrap();
+        bet(&mut deps, "alice", &coins(100, "uust"), BetOutcome::Host).unwrap();
+        match claim(&mut deps, "alice") {
+            Err(ContractError::BetsNotSettled {}) => {}
+            res => panic!("Must return BetsNotSettled error, got {:?}", res),
+        }
+
+        play(&mut deps, "anyone", 0, 0).unwrap();
+        play(&mut deps, "anyone_else", 0, 1).unwrap();
+        play(&mut deps, "anyone", 1, 0).unwrap();
+        play(&mut deps, "anyone_else", 1, 1).unwrap();
+        match bet(&mut deps, "carol", &coins(100, "uust"), BetOutcome::Draw) {
+            Err(ContractError::BettingClosed {}) => {}
+            res => panic!("Must return BettingClosed error, got {:?}", res),
+        }
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetBetMarket { game_id: 1 },
+        )
+        .unwrap();
+        let value: BetMarketResponse = from_binary(&res).unwrap();
+        assert!(!value.open);
+        assert_eq!(Uint128::new(300), value.market.pools.host);
+        assert_eq!(Uint128::new(100), value.market.pools.opponent);
+
+        play(&mut deps, "anyone", 2, 0).unwrap();
+        play(&mut deps, "anyone_else", 3, 3).unwrap();
+        play(&mut deps, "anyone", 3, 0).unwrap();
+
+        // The 2% house fee of the 400 pool goes to the fee pool
+        let res = claim(&mut deps, "alice").unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(392, "uust"),
+            })],
+            res.messages
+        );
+        let res = claim(&mut deps, "bob").unwrap();
+        assert!(res.messages.is_empty());
+        match claim(&mut deps, "alice") {
+            Err(ContractError::NoBetToClaim {}) => {}
+            res => panic!("Must return NoBetToClaim error, got {:?}", res),
+        }
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetBet {
+                game_id: 1,
+                address: "alice".to_string(),
             },
-            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        )
+        .unwrap();
+        let value: BetResponse = from_binary(&res).unwrap();
+        assert_eq!(BetPosition::default(), value.position);
+    }
+
+    #[test]
+    fn rematch_swaps_the_first_player() {
+        let mut deps = setup_game_in_progress();
+        let rematch = |deps: &mut MockDeps, player: &str, funds: &[Coin], msg: ExecuteMsg| {
+            execute(deps.as_mut(), mock_env(), mock_info(player, funds), msg)
+        };
+        let stake = coins(100_000_000, "uust");
+
+        match rematch(
+            &mut deps,
+            "anyone",
+            &stake,
+            ExecuteMsg::OfferRematch { game_id: 1 },
+        ) {
+            Err(ContractError::NotAllowedInCurrentState { .. }) => {}
+            res => panic!("Must return NotAllowedInCurrentState error, got {:?}", res),
+        }
+        play(&mut deps, "anyone", 0, 0).unwrap();
+        play(&mut deps, "anyone_else", 0, 1).unwrap();
+        play(&mut deps, "anyone", 1, 0).unwrap();
+        play(&mut deps, "anyone_else", 1, 1).unwrap();
+        play(&mut deps, "anyone", 2, 0).unwrap();
+        play(&mut deps, "anyone_else", 3, 3).unwrap();
+        play(&mut deps, "anyone", 3, 0).unwrap();
+
+        match rematch(
+            &mut deps,
+            "anyone_else",
+            &coins(50_000_000, "uust"),
+            ExecuteMsg::OfferRematch { game_id: 1 },
+        ) {
+            Err(ContractError::BetAmounTooLow {}) => {}
+            res => panic!("Must return BetAmounTooLow error, got {:?}", res),
+        }
+        rematch(
+            &mut deps,
+            "anyone_else",
+            &stake,
+            ExecuteMsg::OfferRematch { game_id: 1 },
+        )
+        .unwrap();
+        match rematch(
+            &mut deps,
+            "anyone",
+            &stake,
+            ExecuteMsg::OfferRematch { game_id: 1 },
+        ) {
+            Err(ContractError::OfferAlreadyPending {}) => {}
+            res => panic!("Must return OfferAlreadyPending error, got {:?}", res),
+        }
+        match rematch(
+            &mut deps,
+            "anyone_else",
+            &stake,
+            ExecuteMsg::AcceptRematch { game_id: 1 },
+        ) {
+            Err(ContractError::NoPendingOffer {}) => {}
+            res => panic!("Must return NoPendingOffer error, got {:?}", res),
+        }
+        rematch(
+            &mut deps,
+            "anyone",
+            &stake,
+            ExecuteMsg::AcceptRematch { game_id: 1 },
+        )
+        .unwrap();
+
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::InProgress, game.state);
+        assert_eq!(Addr::unchecked("anyone_else"), game.host);
+        assert_eq!(Addr::unchecked("anyone_else"), game.next_player);
+        assert_eq!(Addr::unchecked("anyone"), game.opponent);
+        assert_eq!(load_game(&deps, 1).bet, game.bet);
+        assert_eq!(Some(1), game.series_id);
+        assert_eq!(Some(1), load_game(&deps, 1).series_id);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetSeries { series_id: 1 },
+        )
+        .unwrap();
+        let value: SeriesResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 2], value.series.games);
+        assert_eq!(vec![1, 0], value.series.wins);
+
+        // A declined offer hands the stake back
+        rematch(
+            &mut deps,
+            "anyone",
+            &stake,
+            ExecuteMsg::OfferRematch { game_id: 1 },
+        )
+        .unwrap();
+        let res = rematch(
+            &mut deps,
+            "anyone_else",
+            &[],
+            ExecuteMsg::DeclineRematch { game_id: 1 },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "anyone".to_string(),
+                amount: coins(98_000_000, "uust"),
+            })],
+            res.messages
+        );
+        assert_eq!(None, load_game(&deps, 1).pending_offer);
+    }
+
+    #[test]
+    fn best_of_series_pays_out_on_clinch() {
+        let mut deps = setup_game_in_progress();
+        let stake = coins(100_000_000, "uust");
+        let msg = ExecuteMsg::CreateSeries { best_of: 4 };
+        match execute(deps.as_mut(), mock_env(), mock_info("alice", &stake), msg) {
+            Err(ContractError::InvalidBestOf {}) => {}
+            res => panic!("Must return InvalidBestOf error, got {:?}", res),
+        }
+        let msg = ExecuteMsg::CreateSeries { best_of: 3 };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        ) {
+            Err(ContractError::BetDenomInvalid {}) => {}
+            res => panic!("Must return BetDenomInvalid error, got {:?}", res),
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &stake), msg).unwrap();
+        let msg = ExecuteMsg::JoinSeries { series_id: 1 };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            msg.clone(),
+        ) {
+            Err(ContractError::BetDenomInvalid {}) => {}
+            res => panic!("Must return BetDenomInvalid error, got {:?}", res),
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("bob", &stake), msg).unwrap();
+
+        // The same board comes up every game, the first mover wins it in seven moves
+        let win = |deps: &mut MockDeps, game_id: u64, first: &str, second: &str| {
+            let moves = [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (3, 3), (3, 0)];
+            let mut res = None;
+            for (i, (x, y)) in moves.iter().enumerate() {
+                let player = if i % 2 == 0 { first } else { second };
+                let msg = ExecuteMsg::MakeMove {
+                    game_id,
+                    x: *x,
+                    y: *y,
+                };
+                res =
+                    Some(execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap());
+            }
+            res.unwrap()
+        };
+        let game = load_game(&deps, 2);
+        assert_eq!(Addr::unchecked("alice"), game.next_player);
+        assert!(game.bet.amount.is_zero());
+        let res = win(&mut deps, 2, "alice", "bob");
+        assert!(res.messages.is_empty());
+
+        let game = load_game(&deps, 3);
+        assert_eq!(Addr::unchecked("bob"), game.host);
+        win(&mut deps, 3, "bob", "alice");
+        let res = win(&mut deps, 4, "alice", "bob");
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(196_000_000, "uust"),
+            })],
+            res.messages
+        );
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetSeries { series_id: 1 },
+        )
+        .unwrap();
+        let series = from_binary::<SeriesResponse>(&res).unwrap().series;
+        assert_eq!(SeriesState::Completed, series.state);
+        assert_eq!(Some(Addr::unchecked("alice")), series.winner);
+        assert_eq!(vec![2, 1], series.wins);
+        assert_eq!(vec![2, 3, 4], series.games);
+        match query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 5 }) {
+            Err(_) => {}
+            res => panic!("Must not start another game, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn connect_four_marks_drop() {
+        let mut deps = setup_game_in_progress();
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: None,
+            rule_set: RuleSet::ConnectFour,
+            seats: None,
+            vs_house: false,
+        };
+        let stake = coins(100_000_000, "uust");
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &stake), msg).unwrap();
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 2,
+            referrer: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("bob", &stake), msg).unwrap();
+
+        // Disabled cells stop a mark like marks do
+        let mv = |deps: &mut MockDeps, player: &str, y: u8| {
+            let msg = ExecuteMsg::MakeMove {
+                game_id: 2,
+                x: 0,
+                y,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap()
+        };
+        let res = mv(&mut deps, "alice", 5);
+        assert_eq!(
+            vec![Event::new("move_made")
+                .add_attribute("game_id", "2")
+                .add_attribute("player", "alice")
+                .add_attribute("move_no", "1")
+                .add_attribute("x", "1")
+                .add_attribute("y", "5")],
+            res.events
+        );
+        mv(&mut deps, "bob", 1);
+        mv(&mut deps, "alice", 0);
+        mv(&mut deps, "bob", 1);
+        mv(&mut deps, "alice", 0);
+        mv(&mut deps, "bob", 1);
+        mv(&mut deps, "alice", 0);
+        mv(&mut deps, "bob", 4);
+        let game = load_game(&deps, 2);
+        assert_eq!(vec![1, 100, 0, 0, 0, 0], game.grid[3]);
+        assert_eq!(GameState::InProgress, game.state);
+        mv(&mut deps, "alice", 0);
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::Completed, game.state);
+        assert_eq!(Addr::unchecked("alice"), game.winner);
+
+        let msg = QueryMsg::GetGames {
+            rule_set: Some(RuleSet::ConnectFour),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: GamesResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![game], value.games);
+        let msg = QueryMsg::GetGamesByAddress {
+            address: "anyone".to_string(),
+            rule_set: Some(RuleSet::Gomoku),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: GamesResponse = from_binary(&res).unwrap();
+        assert!(value.games.is_empty());
+    }
+
+    #[test]
+    fn three_seat_game_starts_when_full() {
+        let mut deps = setup_game_in_progress();
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: None,
+            rule_set: RuleSet::Classic,
+            seats: Some(3),
+            vs_house: false,
+        };
+        let stake = coins(100_000_000, "uust");
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &stake), msg).unwrap();
+        let join = |deps: &mut MockDeps, player: &str| {
+            let msg = ExecuteMsg::JoinGame {
+                game_id: 2,
+                referrer: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &stake), msg)
         };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        join(&mut deps, "bob").unwrap();
+        assert_eq!(GameState::New, load_game(&deps, 2).state);
+        match join(&mut deps, "bob") {
+            Err(ContractError::NotAllowedToJoin {}) => {}
+            _ => panic!("Must return NotAllowedToJoin error"),
+        }
+        join(&mut deps, "carol").unwrap();
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::InProgress, game.state);
+        assert_eq!(coins(294_000_000, "uust"), vec![game.bet]);
 
-        let anyone = mock_info("anyone", &coins(100_000_000, "uust"));
-        for _ in 0..5 {
-            let msg = ExecuteMsg::CreateGame {};
-            let _res = execute(deps.as_mut(), mock_env(), anyone.clone(), msg).unwrap();
+        // Turns rotate through the seats, each seat with a mark of its own
+        let mv = |deps: &mut MockDeps, player: &str, x: u8, y: u8| {
+            let msg = ExecuteMsg::MakeMove { game_id: 2, x, y };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg)
+        };
+        mv(&mut deps, "alice", 0, 0).unwrap();
+        match mv(&mut deps, "alice", 0, 1) {
+            Err(ContractError::NotYourTurn {}) => {}
+            _ => panic!("Must return NotYourTurn error"),
         }
+        mv(&mut deps, "bob", 0, 1).unwrap();
+        mv(&mut deps, "carol", 1, 0).unwrap();
+        let game = load_game(&deps, 2);
+        assert_eq!(
+            vec![1, 100, 2],
+            vec![game.grid[0][0], game.grid[0][1], game.grid[1][0]]
+        );
+        assert_eq!(Addr::unchecked("alice"), game.next_player);
 
-        // All games
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGames {}).unwrap();
+        let msg = QueryMsg::GetGamesByAddress {
+            address: "carol".to_string(),
+            rule_set: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
         let value: GamesResponse = from_binary(&res).unwrap();
-        assert_eq!(5, value.games.len());
+        assert_eq!(vec![game.clone()], value.games);
+        let msg = QueryMsg::GetGamesByAddress {
+            address: "alice".to_string(),
+            rule_set: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: GamesResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![game], value.games);
 
-        // Games by address
-        let res = query(
-            deps.as_ref(),
+        // A resigning seat leaves its stake in the pot and the others play on without it
+        let resign = |deps: &mut MockDeps, player: &str| {
+            let msg = ExecuteMsg::Resign { game_id: 2 };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg)
+        };
+        let res = resign(&mut deps, "carol").unwrap();
+        assert!(res.messages.is_empty());
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::InProgress, game.state);
+        assert_eq!(vec![Addr::unchecked("carol")], game.resigned);
+        match resign(&mut deps, "carol") {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        mv(&mut deps, "alice", 1, 1).unwrap();
+        mv(&mut deps, "bob", 2, 0).unwrap();
+        assert_eq!(Addr::unchecked("alice"), load_game(&deps, 2).next_player);
+
+        // The last player left wins the whole pot
+        resign(&mut deps, "bob").unwrap();
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::Completed, game.state);
+        assert_eq!(Addr::unchecked("alice"), game.winner);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetLeaderboard {}).unwrap();
+        let value: LeaderboardResponse = from_binary(&res).unwrap();
+        assert!(value
+            .entries
+            .iter()
+            .any(|entry| entry.player == Addr::unchecked("alice") && entry.win_count == 1));
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 2 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(294_000_000, "uust"),
+            })],
+            res.messages
+        );
+    }
+
+    #[test]
+    fn teammates_share_a_seat() {
+        let mut deps = setup_game_in_progress();
+        let stake = coins(100_000_000, "uust");
+        execute(
+            deps.as_mut(),
             mock_env(),
-            QueryMsg::GetGamesByAddress {
-                address: "anyone".to_string(),
-            },
+            mock_info("alice", &stake),
+            create_game_msg(),
         )
         .unwrap();
+        let add = |deps: &mut MockDeps, player: &str, teammate: &str| {
+            let msg = ExecuteMsg::AddTeammate {
+                game_id: 2,
+                teammate: teammate.to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg)
+        };
+        let res = add(&mut deps, "alice", "dave").unwrap();
+        assert_eq!(
+            vec![Event::new("teammate_added")
+                .add_attribute("game_id", "2")
+                .add_attribute("player", "alice")
+                .add_attribute("teammate", "dave")],
+            res.events
+        );
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 2,
+            referrer: None,
+        };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("dave", &stake),
+            msg.clone(),
+        ) {
+            Err(ContractError::NotAllowedToJoin {}) => {}
+            _ => panic!("Must return NotAllowedToJoin error"),
+        }
+        match add(&mut deps, "dave", "erin") {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("bob", &stake), msg).unwrap();
+        match add(&mut deps, "bob", "erin") {
+            Err(ContractError::NotAllowedInCurrentState { .. }) => {}
+            _ => panic!("Must return NotAllowedInCurrentState error"),
+        }
+
+        // Either member moves for the seat, but only on its turn
+        let mv = |deps: &mut MockDeps, player: &str, x: u8, y: u8| {
+            let msg = ExecuteMsg::MakeMove { game_id: 2, x, y };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg)
+        };
+        mv(&mut deps, "dave", 0, 0).unwrap();
+        match mv(&mut deps, "dave", 0, 1) {
+            Err(ContractError::NotYourTurn {}) => {}
+            _ => panic!("Must return NotYourTurn error"),
+        }
+        mv(&mut deps, "bob", 0, 1).unwrap();
+        mv(&mut deps, "alice", 1, 0).unwrap();
+        mv(&mut deps, "bob", 1, 1).unwrap();
+        mv(&mut deps, "dave", 2, 0).unwrap();
+        mv(&mut deps, "bob", 3, 3).unwrap();
+        mv(&mut deps, "dave", 3, 0).unwrap();
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::Completed, game.state);
+        assert_eq!(Addr::unchecked("alice"), game.winner);
+        let msg = QueryMsg::GetGamesByAddress {
+            address: "dave".to_string(),
+            rule_set: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
         let value: GamesResponse = from_binary(&res).unwrap();
-        assert_eq!(5, value.games.len());
+        assert_eq!(vec![game], value.games);
+
+        // The winnings are split among the team on withdrawal
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 2 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("dave", &[]), msg).unwrap();
+        assert_eq!(
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "alice".to_string(),
+                    amount: coins(98_000_000, "uust"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "dave".to_string(),
+                    amount: coins(98_000_000, "uust"),
+                })
+            ],
+            res.messages
+        );
     }
 
     #[test]
-    fn join_game() {
-        let mut deps = mock_dependencies(&coins(2, "token"));
+    fn house_replies_within_the_move() {
+        let mut deps = setup_game_in_progress();
+        let msg = ExecuteMsg::UpdateHouse {
+            exposure_bps: 5_000,
+            max_stake_bps: 5_000,
+            edge_bps: 5_000,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let bankroll = coins(1_000_000_000, "uust");
+        let msg = ExecuteMsg::DepositHouse {};
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("provider", &bankroll),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![Event::new("house_deposited")
+                .add_attribute("provider", "provider")
+                .add_attribute("amount", "1000000000uust")
+                .add_attribute("shares", "1000000000")],
+            res.events
+        );
 
-        let msg = InstantiateMsg {
-            min_bet: Coin {
-                amount: Uint128::new(10_000_000),
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: None,
+            rule_set: RuleSet::Classic,
+            seats: None,
+            vs_house: true,
+        };
+        let stake = coins(600_000_000, "uust");
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &stake),
+            msg.clone(),
+        );
+        match res {
+            Err(ContractError::HouseExposureExceeded {}) => {}
+            _ => panic!("Must return HouseExposureExceeded error"),
+        }
+        let stake = coins(100_000_000, "uust");
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &stake), msg).unwrap();
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::InProgress, game.state);
+        assert_eq!(Addr::unchecked(MOCK_CONTRACT_ADDR), game.opponent);
+        assert_eq!(coins(196_000_000, "uust"), vec![game.bet]);
+
+        let query_house = |deps: &MockDeps| {
+            let msg = QueryMsg::GetHouse {
                 denom: "uust".to_string(),
-            },
-            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let value: HouseResponse = from_binary(&res).unwrap();
+            value
         };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        // Half of the fee is the edge of the house
+        let value = query_house(&deps);
+        assert_eq!(Uint128::new(903_000_000), value.house.bankroll);
+        assert_eq!(Uint128::new(98_000_000), value.house.exposure);
+        assert_eq!(Uint128::new(500_500_000), value.max_exposure);
 
-        let unauth_info = mock_info("anyone", &coins(100_000_000, "uust"));
-        let msg = ExecuteMsg::CreateGame {};
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        // The bankroll left has to cover the running game
+        let withdraw = |deps: &mut MockDeps| {
+            let msg = ExecuteMsg::WithdrawHouse {
+                denom: "uust".to_string(),
+                shares: Uint128::new(1_000_000_000),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("provider", &[]), msg)
+        };
+        match withdraw(&mut deps) {
+            Err(ContractError::InsufficientReserves {}) => {}
+            _ => panic!("Must return InsufficientReserves error"),
+        }
 
-        let unauth_info = mock_info("anyone_else", &coins(100_000_000, "uust"));
-        let msg = ExecuteMsg::JoinGame { game_id: 1 };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 2,
+            x: 0,
+            y: 0,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        let movers: Vec<&str> = res
+            .events
+            .iter()
+            .filter(|event| event.ty == "move_made")
+            .map(|event| event.attributes[1].value.as_str())
+            .collect();
+        assert_eq!(vec!["alice", MOCK_CONTRACT_ADDR], movers);
+        let game = load_game(&deps, 2);
+        assert_eq!(2, game.move_count);
+        assert_eq!(Addr::unchecked("alice"), game.next_player);
 
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
-        let value: GameResponse = from_binary(&res).unwrap();
-        assert_eq!(GameState::InProgress, value.game.state);
-        assert_eq!("anyone_else", value.game.opponent.to_string());
-        assert_eq!("anyone", value.game.host.to_string());
+        // A win of the house goes straight back into the bankroll
+        let msg = ExecuteMsg::Resign { game_id: 2 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::PriceWithdrawn, game.state);
+        assert_eq!(Addr::unchecked(MOCK_CONTRACT_ADDR), game.winner);
+        let value = query_house(&deps);
+        assert_eq!(Uint128::new(1_099_000_000), value.house.bankroll);
+        assert!(value.house.exposure.is_zero());
+
+        let msg = QueryMsg::GetHouseShares {
+            denom: "uust".to_string(),
+            address: "provider".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: HouseSharesResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(1_099_000_000), value.value);
+        let res = withdraw(&mut deps).unwrap();
         assert_eq!(
-            Coin {
-                amount: Uint128::new(196_000_000),
-                denom: "uust".to_string()
-            },
-            value.game.bet
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "provider".to_string(),
+                amount: coins(1_099_000_000, "uust"),
+            })],
+            res.messages
         );
     }
 
     #[test]
-    fn new_game_can_be_cancelled() {
-        let mut deps = mock_dependencies(&coins(2, "token"));
-
-        let msg = InstantiateMsg {
-            min_bet: Coin {
-                amount: Uint128::new(10_000_000),
-                denom: "uust".to_string(),
-            },
-            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+    fn house_taking_the_last_cell_draws_the_game() {
+        let mut deps = setup_game_in_progress();
+        let msg = ExecuteMsg::UpdateHouse {
+            exposure_bps: 5_000,
+            max_stake_bps: 5_000,
+            edge_bps: 5_000,
         };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let bankroll = coins(1_000_000_000, "uust");
+        let msg = ExecuteMsg::DepositHouse {};
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("provider", &bankroll),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: None,
+            rule_set: RuleSet::Classic,
+            seats: None,
+            vs_house: true,
+        };
+        let stake = coins(100_000_000, "uust");
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &stake), msg).unwrap();
 
-        let unauth_info = mock_info("anyone", &coins(100_000_000, "uust"));
-        let msg = ExecuteMsg::CreateGame {};
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        // Eight playable cells, the last two left free and neither of them winning
+        let mut game = load_game(&deps, 2);
+        game.rules = BoardRules {
+            dimension: 3,
+            threshold: 3,
+        };
+        game.grid = vec![vec![1, 100, 1], vec![100, 100, 0], vec![0, 1, -1]];
+        game.move_count = 6;
+        games()
+            .save(deps.as_mut().storage, U64Key::new(2), &game)
+            .unwrap();
 
-        let unauth_info = mock_info("anyone", &coins(2, "token"));
-        let msg = ExecuteMsg::CancelGame { game_id: 1 };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        let msg = ExecuteMsg::MakeMove {
+            game_id: 2,
+            x: 1,
+            y: 2,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        let game = load_game(&deps, 2);
+        assert_eq!(100, game.grid[2][0]);
+        assert_eq!(GameState::Drawn, game.state);
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(98_000_000, "uust"),
+            })],
+            res.messages
+        );
+        let msg = QueryMsg::GetHouse {
+            denom: "uust".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: HouseResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(1_001_000_000), value.house.bankroll);
+        assert!(value.house.exposure.is_zero());
+    }
 
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
-        let value: GameResponse = from_binary(&res).unwrap();
-        assert_eq!(GameState::Cancelled, value.game.state);
+    #[test]
+    fn session_keys_move_but_never_move_funds() {
+        let mut deps = setup_game_in_progress();
+        let msg = ExecuteMsg::AuthorizeSessionKey {
+            key: "session".to_string(),
+            game_id: Some(1),
+            duration: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            vec![Event::new("session_key_authorized")
+                .add_attribute("key", "session")
+                .add_attribute("player", "anyone")
+                .add_attribute("game_id", "1")
+                .add_attribute("expires_at", "")],
+            res.events
+        );
+        // A key acts for a single player
+        let msg = ExecuteMsg::AuthorizeSessionKey {
+            key: "session".to_string(),
+            game_id: None,
+            duration: Some(600),
+        };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        ) {
+            Err(ContractError::InvalidSessionKey {}) => {}
+            _ => panic!("Must return InvalidSessionKey error"),
+        }
+        // Nor can it bet on the game of its player
+        let msg = ExecuteMsg::PlaceBet {
+            game_id: 1,
+            outcome: BetOutcome::Opponent,
+        };
+        let spectator = mock_info("session", &coins(10_000_000, "uust"));
+        match execute(deps.as_mut(), mock_env(), spectator, msg) {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
 
-        // match res {
-        //     Err(ContractError::Unauthorized {}) => {}
-        //     _ => panic!("Must return unauthorized error"),
-        // }
+        play(&mut deps, "session", 0, 0).unwrap();
+        match play(&mut deps, "session", 0, 1) {
+            Err(ContractError::NotYourTurn {}) => {}
+            _ => panic!("Must return NotYourTurn error"),
+        }
+        play(&mut deps, "anyone_else", 0, 1).unwrap();
+        play(&mut deps, "session", 1, 0).unwrap();
+        play(&mut deps, "anyone_else", 1, 1).unwrap();
+        play(&mut deps, "session", 2, 0).unwrap();
+        play(&mut deps, "anyone_else", 3, 3).unwrap();
+        play(&mut deps, "session", 3, 0).unwrap();
+        let game = load_game(&deps, 1);
+        assert_eq!(Addr::unchecked("anyone"), game.winner);
+        let msg = QueryMsg::GetMoves {
+            game_id: 1,
+            start_after: None,
+            limit: Some(1),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: MovesResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("anyone"), value.moves[0].player);
 
-        // // only the original creator can reset the counter
-        // let auth_info = mock_info("creator", &coins(2, "token"));
-        // let msg = ExecuteMsg::Reset { count: 5 };
-        // let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+        let msg = ExecuteMsg::WithdrawPrice { game_id: 1 };
+        match execute(deps.as_mut(), mock_env(), mock_info("session", &[]), msg) {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
 
-        // // should now be 5
-        // let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
-        // let value: CountResponse = from_binary(&res).unwrap();
-        // assert_eq!(5, value.count);
+        let msg = ExecuteMsg::RevokeSessionKey {
+            key: "session".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        let msg = QueryMsg::GetSessionKey {
+            key: "session".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: SessionKeyResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.session);
     }
 
     #[test]
-    fn full_game_until_host_wins() {
-        let mut deps = mock_dependencies(&coins(2, "token"));
-        deps.querier.with_terrand();
+    fn ultimate_game_is_played_on_sub_boards() {
+        let mut deps = setup_game_in_progress();
+        let msg = ExecuteMsg::CreateGame {
+            auto_payout: false,
+            referrer: None,
+            rule_set: RuleSet::Ultimate,
+            seats: None,
+            vs_house: false,
+        };
+        let stake = coins(100_000_000, "uust");
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &stake), msg).unwrap();
+        let msg = ExecuteMsg::JoinGame {
+            game_id: 2,
+            referrer: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("bob", &stake), msg).unwrap();
+        let game = load_game(&deps, 2);
+        assert!(game.grid.is_empty());
+        assert_eq!(Some(ultimate::new_board()), game.ultimate);
 
-        let msg = InstantiateMsg {
-            min_bet: Coin {
-                amount: Uint128::new(10_000_000),
-                denom: "uust".to_string(),
-            },
-            terrand_address: MOCK_CONTRACT_ADDR.to_string(),
+        let mv = |deps: &mut MockDeps, player: &str, x: u8, y: u8| {
+            let msg = ExecuteMsg::MakeMove { game_id: 2, x, y };
+            execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg)
         };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        mv(&mut deps, "alice", 7, 8).unwrap();
+        // Cell 5 of sub-board 8 sends bob to sub-board 5
+        match mv(&mut deps, "bob", 0, 0) {
+            Err(ContractError::WrongSubBoard {}) => {}
+            res => panic!("Must return WrongSubBoard error, got {:?}", res),
+        }
+        let msg = ExecuteMsg::RequestTakeback { game_id: 2 };
+        match execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg) {
+            Err(ContractError::TakebackNotAllowed {}) => {}
+            res => panic!("Must return TakebackNotAllowed error, got {:?}", res),
+        }
 
-        let unauth_info = mock_info("anyone", &coins(100_000_000, "uust"));
-        let msg = ExecuteMsg::CreateGame {};
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        let moves = [
+            (5, 8),
+            (7, 6),
+            (5, 1),
+            (6, 3),
+            (2, 0),
+            (6, 2),
+            (2, 8),
+            (7, 7),
+            (5, 4),
+            (8, 5),
+            (8, 3),
+            (8, 2),
+            (5, 0),
+            (7, 2),
+            (5, 7),
+        ];
+        for (i, (x, y)) in moves.iter().enumerate() {
+            let player = if i % 2 == 0 { "bob" } else { "alice" };
+            mv(&mut deps, player, *x, *y).unwrap();
+        }
+        let board = load_game(&deps, 2).ultimate.unwrap();
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 1, 0, 1], board.boards);
 
-        let unauth_info = mock_info("anyone_else", &coins(100_000_000, "uust"));
-        let msg = ExecuteMsg::JoinGame { game_id: 1 };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        mv(&mut deps, "alice", 7, 4).unwrap();
+        let game = load_game(&deps, 2);
+        assert_eq!(GameState::Completed, game.state);
+        assert_eq!(Addr::unchecked("alice"), game.winner);
+        assert_eq!(1, game.ultimate.unwrap().boards[7]);
+    }
 
-        // X to [0,0]
-        let unauth_info = mock_info("anyone", &coins(100, "uust"));
-        let msg = ExecuteMsg::MakeMove {
-            game_id: 1,
-            x: 0,
-            y: 0,
+    fn move_key(deps: &mut MockDeps, player: &str, seed: u8) -> k256::ecdsa::SigningKey {
+        let key = k256::ecdsa::SigningKey::from_bytes(&[seed; 32]).unwrap();
+        let msg = ExecuteMsg::RegisterMoveKey {
+            pubkey: Binary::from(key.verifying_key().to_bytes().as_slice()),
         };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(player, &[]), msg).unwrap();
+        key
+    }
 
-        // O to [0,1]
-        let unauth_info = mock_info("anyone_else", &coins(100, "uust"));
-        let msg = ExecuteMsg::MakeMove {
+    fn signed_move(
+        move_no: u32,
+        x: u8,
+        y: u8,
+        signers: &[(&str, &k256::ecdsa::SigningKey)],
+    ) -> SignedMove {
+        signed_move_after(0, move_no, x, y, signers)
+    }
+
+    fn signed_move_after(
+        takebacks: u32,
+        move_no: u32,
+        x: u8,
+        y: u8,
+        signers: &[(&str, &k256::ecdsa::SigningKey)],
+    ) -> SignedMove {
+        use k256::ecdsa::signature::Signer;
+        let message = crate::signed::move_message(&mock_env(), 1, takebacks, move_no, x, y);
+        SignedMove {
+            takebacks,
+            move_no,
+            x,
+            y,
+            signatures: signers
+                .iter()
+                .map(|(player, key)| {
+                    let signature: k256::ecdsa::Signature = key.sign(&message);
+                    MoveSignature {
+                        player: Addr::unchecked(*player),
+                        signature: Binary::from(signature.as_ref()),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn signed_moves_replay_an_off_chain_game() {
+        let mut deps = setup_game_in_progress();
+        let host = move_key(&mut deps, "anyone", 1);
+        let opponent = move_key(&mut deps, "anyone_else", 2);
+        let msg = QueryMsg::GetMoveKey {
+            address: "anyone".to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: MoveKeyResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Some(Binary::from(host.verifying_key().to_bytes().as_slice())),
+            value.pubkey
+        );
+
+        let both = [("anyone", &host), ("anyone_else", &opponent)];
+        let cells = [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (3, 3), (3, 0)];
+        let mut moves: Vec<SignedMove> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| signed_move(i as u32 + 1, *x, *y, &both))
+            .collect();
+        moves[6] = signed_move(7, 3, 0, &[("anyone", &host)]);
+
+        // Only the last move may lack the countersignature
+        let mut unsigned = moves.clone();
+        unsigned[1] = signed_move(2, 0, 1, &[("anyone_else", &opponent)]);
+        let msg = ExecuteMsg::SubmitSignedMoves {
             game_id: 1,
-            x: 0,
-            y: 1,
+            moves: unsigned,
         };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        match execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg) {
+            Err(ContractError::MissingSignature {}) => {}
+            _ => panic!("Must return MissingSignature error"),
+        }
+        let msg = ExecuteMsg::SubmitSignedMoves {
+            game_id: 1,
+            moves: moves[1..].to_vec(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg) {
+            Err(ContractError::MoveOutOfOrder {}) => {}
+            _ => panic!("Must return MoveOutOfOrder error"),
+        }
 
-        // X to [1,0]
-        let unauth_info = mock_info("anyone", &coins(100, "uust"));
-        let msg = ExecuteMsg::MakeMove {
+        let msg = ExecuteMsg::SubmitSignedMoves { game_id: 1, moves };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+        assert!(res.events.contains(
+            &Event::new("signed_moves_submitted")
+                .add_attribute("game_id", "1")
+                .add_attribute("submitter", "relayer")
+                .add_attribute("count", "7")
+        ));
+        let game = load_game(&deps, 1);
+        assert_eq!(GameState::Completed, game.state);
+        assert_eq!(Addr::unchecked("anyone"), game.winner);
+        assert_eq!(7, game.move_count);
+    }
+
+    #[test]
+    fn equivocation_loses_the_game() {
+        let mut deps = setup_game_in_progress();
+        let host = move_key(&mut deps, "anyone", 1);
+        let opponent = move_key(&mut deps, "anyone_else", 2);
+        let both = [("anyone", &host), ("anyone_else", &opponent)];
+        let msg = ExecuteMsg::SubmitSignedMoves {
             game_id: 1,
-            x: 1,
-            y: 0,
+            moves: vec![signed_move(1, 0, 0, &both)],
         };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
 
-        // O to [1,1]
-        let unauth_info = mock_info("anyone_else", &coins(100, "uust"));
-        let msg = ExecuteMsg::MakeMove {
+        let first = signed_move(2, 0, 1, &[("anyone_else", &opponent)]);
+        let msg = ExecuteMsg::ReportEquivocation {
             game_id: 1,
-            x: 1,
-            y: 1,
+            player: "anyone_else".to_string(),
+            first: first.clone(),
+            second: first.clone(),
         };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        match execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg) {
+            Err(ContractError::NoEquivocation {}) => {}
+            _ => panic!("Must return NoEquivocation error"),
+        }
 
-        // X to [2,0]
-        let unauth_info = mock_info("anyone", &coins(100, "uust"));
-        let msg = ExecuteMsg::MakeMove {
+        let second = signed_move(2, 1, 1, &[("anyone_else", &opponent)]);
+        let msg = ExecuteMsg::ReportEquivocation {
             game_id: 1,
-            x: 2,
-            y: 0,
+            player: "anyone_else".to_string(),
+            first,
+            second,
         };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert!(res.events.contains(
+            &Event::new("equivocation_reported")
+                .add_attribute("game_id", "1")
+                .add_attribute("player", "anyone_else")
+                .add_attribute("move_no", "2")
+        ));
+        let game = load_game(&deps, 1);
+        assert_eq!(GameState::Completed, game.state);
+        assert_eq!(Addr::unchecked("anyone"), game.winner);
+    }
 
-        // O to [3,3]
-        let unauth_info = mock_info("anyone_else", &coins(100, "uust"));
-        let msg = ExecuteMsg::MakeMove {
+    #[test]
+    fn takebacks_void_the_moves_signed_before() {
+        let mut deps = setup_game_in_progress();
+        let host = move_key(&mut deps, "anyone", 1);
+        let opponent = move_key(&mut deps, "anyone_else", 2);
+        let both = [("anyone", &host), ("anyone_else", &opponent)];
+        let taken_back = signed_move(2, 0, 1, &both);
+        let msg = ExecuteMsg::SubmitSignedMoves {
             game_id: 1,
-            x: 3,
-            y: 3,
+            moves: vec![signed_move(1, 0, 0, &both), taken_back.clone()],
         };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
 
-        // X to [3,0]
-        let unauth_info = mock_info("anyone", &coins(100, "uust"));
-        let msg = ExecuteMsg::MakeMove {
+        let msg = ExecuteMsg::RequestTakeback { game_id: 1 };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone_else", &[]),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::AcceptTakeback { game_id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(1, load_game(&deps, 1).takebacks);
+
+        // The move taken back cannot be submitted again to undo the takeback
+        let msg = ExecuteMsg::SubmitSignedMoves {
             game_id: 1,
-            x: 3,
-            y: 0,
+            moves: vec![taken_back.clone()],
         };
-        let _res = execute(deps.as_mut(), mock_env(), unauth_info, msg).unwrap();
+        match execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg) {
+            Err(ContractError::MoveOutOfOrder {}) => {}
+            _ => panic!("Must return MoveOutOfOrder error"),
+        }
 
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetGameById { id: 1 }).unwrap();
-        let value: GameResponse = from_binary(&res).unwrap();
-        assert_eq!(GameState::Completed, value.game.state);
-        assert_eq!("anyone", value.game.winner.to_string());
+        let retaken = signed_move_after(1, 2, 1, 1, &both);
+        let msg = ExecuteMsg::SubmitSignedMoves {
+            game_id: 1,
+            moves: vec![retaken.clone()],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+        let game = load_game(&deps, 1);
+        assert_eq!(0, game.grid[0][1]);
+        assert_eq!(100, game.grid[1][1]);
+        assert_eq!(2, game.move_count);
 
-        // Check leaderboard
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetLeaderboard {}).unwrap();
-        let value: LeaderboardResponse = from_binary(&res).unwrap();
-        assert_eq!(1, value.entries[0].win_count);
-        assert_eq!("anyone", value.entries[0].player);
+        // Making the move taken back again is no equivocation
+        let msg = ExecuteMsg::ReportEquivocation {
+            game_id: 1,
+            player: "anyone_else".to_string(),
+            first: taken_back,
+            second: retaken,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg) {
+            Err(ContractError::NoEquivocation {}) => {}
+            _ => panic!("Must return NoEquivocation error"),
+        }
+        assert_eq!(GameState::InProgress, load_game(&deps, 1).state);
     }
 }
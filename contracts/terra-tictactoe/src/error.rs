@@ -47,6 +47,135 @@ pub enum ContractError {
     
     #[error("PriceCannotBeWithdrawn")]
     PriceCannotBeWithdrawn {},
+
+    #[error("OfferAlreadyPending")]
+    OfferAlreadyPending {},
+
+    #[error("NoPendingOffer")]
+    NoPendingOffer {},
+
+    #[error("TakebackNotAllowed")]
+    TakebackNotAllowed {},
+
+    #[error("InvalidFeeBps")]
+    InvalidFeeBps {},
+
+    #[error("InvalidFeeCaps")]
+    InvalidFeeCaps {},
+
+    #[error("StakeBelowFee")]
+    StakeBelowFee {},
+
+    #[error("InvalidReferrer")]
+    InvalidReferrer {},
+
+    #[error("InvalidReferralPercentage")]
+    InvalidReferralPercentage {},
+
+    #[error("NoReferralRewards")]
+    NoReferralRewards {},
+
+    #[error("InvalidFeeDistribution")]
+    InvalidFeeDistribution {},
+
+    #[error("NoFeesToDistribute")]
+    NoFeesToDistribute {},
+
+    #[error("NoJackpotDraw")]
+    NoJackpotDraw {},
+
+    #[error("JackpotDrawPending")]
+    JackpotDrawPending {},
+
+    #[error("TournamentNotFound")]
+    TournamentNotFound {},
+
+    #[error("InvalidBracketSize")]
+    InvalidBracketSize {},
+
+    #[error("InvalidPrizeDistribution")]
+    InvalidPrizeDistribution {},
+
+    #[error("RegistrationClosed")]
+    RegistrationClosed {},
+
+    #[error("AlreadyRegistered")]
+    AlreadyRegistered {},
+
+    #[error("EntryFeeInvalid")]
+    EntryFeeInvalid {},
+
+    #[error("DrawNotAllowed")]
+    DrawNotAllowed {},
+
+    #[error("LeagueNotConfigured")]
+    LeagueNotConfigured {},
+
+    #[error("InvalidSeasonDuration")]
+    InvalidSeasonDuration {},
+
+    #[error("SeasonFull")]
+    SeasonFull {},
+
+    #[error("InvalidRewardDistribution")]
+    InvalidRewardDistribution {},
+
+    #[error("BettingClosed")]
+    BettingClosed {},
+
+    #[error("BetsNotSettled")]
+    BetsNotSettled {},
+
+    #[error("NoBetToClaim")]
+    NoBetToClaim {},
+
+    #[error("RematchNotAllowed")]
+    RematchNotAllowed {},
+
+    #[error("SeriesNotFound")]
+    SeriesNotFound {},
+
+    #[error("InvalidBestOf")]
+    InvalidBestOf {},
+
+    #[error("WrongSubBoard")]
+    WrongSubBoard {},
+
+    #[error("InvalidSeats")]
+    InvalidSeats {},
+
+    #[error("TwoPlayersOnly")]
+    TwoPlayersOnly {},
+
+    #[error("TeamFull")]
+    TeamFull {},
+
+    #[error("HouseExposureExceeded")]
+    HouseExposureExceeded {},
+
+    #[error("HouseGameNotSupported")]
+    HouseGameNotSupported {},
+
+    #[error("InsufficientReserves")]
+    InsufficientReserves {},
+
+    #[error("InvalidSessionKey")]
+    InvalidSessionKey {},
+
+    #[error("InvalidPublicKey")]
+    InvalidPublicKey {},
+
+    #[error("InvalidSignature")]
+    InvalidSignature {},
+
+    #[error("MissingSignature")]
+    MissingSignature {},
+
+    #[error("MoveOutOfOrder")]
+    MoveOutOfOrder {},
+
+    #[error("NoEquivocation")]
+    NoEquivocation {},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
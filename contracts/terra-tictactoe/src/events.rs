@@ -0,0 +1,401 @@
+use cosmwasm_std::{Addr, Coin, Event, Uint128};
+
+use crate::state::{
+    BetOutcome, Config, FeeCaps, Game, JackpotDraw, LeaderboardSeason, League, LeagueSeason, Offer,
+    OfferKind, RuleSet, SeasonResult, Series, Session, Tournament,
+};
+
+pub fn game_created(game: &Game) -> Event {
+    Event::new("game_created")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("host", game.host.to_string())
+        .add_attribute("bet", game.bet.to_string())
+        .add_attribute("rule_set", rule_set(game.rule_set))
+}
+
+pub fn referral_credited(game: &Game, player: &Addr, referrer: &Addr, reward: &Coin) -> Event {
+    Event::new("referral_credited")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("player", player.to_string())
+        .add_attribute("referrer", referrer.to_string())
+        .add_attribute("amount", reward.to_string())
+}
+
+pub fn game_joined(game: &Game, player: &Addr) -> Event {
+    Event::new("game_joined")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("opponent", player.to_string())
+        .add_attribute("bet", game.bet.to_string())
+}
+
+pub fn teammate_added(game: &Game, player: &Addr, teammate: &Addr) -> Event {
+    Event::new("teammate_added")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("player", player.to_string())
+        .add_attribute("teammate", teammate.to_string())
+}
+
+pub fn move_made(game: &Game, player: &Addr, x: u8, y: u8) -> Event {
+    Event::new("move_made")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("player", player.to_string())
+        .add_attribute("move_no", game.move_count.to_string())
+        .add_attribute("x", x.to_string())
+        .add_attribute("y", y.to_string())
+}
+
+pub fn game_won(game: &Game) -> Event {
+    Event::new("game_won")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("winner", game.winner.to_string())
+}
+
+pub fn jackpot_draw_scheduled(draw: &JackpotDraw) -> Event {
+    Event::new("jackpot_draw_scheduled")
+        .add_attribute("game_id", draw.game_id.to_string())
+        .add_attribute("winner", draw.winner.to_string())
+        .add_attribute("round", draw.round.to_string())
+}
+
+pub fn jackpot_drawn(draw: &JackpotDraw, hit: bool) -> Event {
+    Event::new("jackpot_drawn")
+        .add_attribute("game_id", draw.game_id.to_string())
+        .add_attribute("round", draw.round.to_string())
+        .add_attribute("hit", hit.to_string())
+}
+
+pub fn jackpot_won(draw: &JackpotDraw, prize: &Coin) -> Event {
+    Event::new("jackpot_won")
+        .add_attribute("game_id", draw.game_id.to_string())
+        .add_attribute("winner", draw.winner.to_string())
+        .add_attribute("amount", prize.to_string())
+}
+
+pub fn game_resigned(game: &Game, player: &Addr) -> Event {
+    Event::new("game_resigned")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("player", player.to_string())
+}
+
+pub fn offer_made(game: &Game, offer: &Offer) -> Event {
+    Event::new("offer_made")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("kind", offer_kind(&offer.kind))
+        .add_attribute("player", offer.from.to_string())
+}
+
+pub fn offer_declined(game: &Game, offer: &Offer) -> Event {
+    Event::new("offer_declined")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("kind", offer_kind(&offer.kind))
+        .add_attribute("player", offer.from.to_string())
+}
+
+pub fn takeback_accepted(game: &Game, player: &Addr) -> Event {
+    Event::new("takeback_accepted")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("player", player.to_string())
+        .add_attribute("move_no", game.move_count.to_string())
+}
+
+pub fn game_drawn(game: &Game) -> Event {
+    Event::new("game_drawn").add_attribute("game_id", game.game_id.to_string())
+}
+
+pub fn rematch_started(previous: &Game, game: &Game) -> Event {
+    Event::new("rematch_started")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("previous_game_id", previous.game_id.to_string())
+        .add_attribute("series_id", game.series_id.unwrap_or_default().to_string())
+        .add_attribute("host", game.host.to_string())
+        .add_attribute("opponent", game.opponent.to_string())
+        .add_attribute("bet", game.bet.to_string())
+}
+
+pub fn series_created(series: &Series) -> Event {
+    Event::new("series_created")
+        .add_attribute("series_id", series.series_id.to_string())
+        .add_attribute("host", series.players[0].to_string())
+        .add_attribute("best_of", series.best_of.unwrap_or_default().to_string())
+        .add_attribute("pot", series.pot.to_string())
+}
+
+pub fn series_started(series: &Series) -> Event {
+    Event::new("series_started")
+        .add_attribute("series_id", series.series_id.to_string())
+        .add_attribute("opponent", series.players[1].to_string())
+        .add_attribute("pot", series.pot.to_string())
+}
+
+pub fn series_game_started(series: &Series, game: &Game) -> Event {
+    let score: Vec<String> = series.wins.iter().map(|w| w.to_string()).collect();
+    Event::new("series_game_started")
+        .add_attribute("series_id", series.series_id.to_string())
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("host", game.host.to_string())
+        .add_attribute("score", score.join("-"))
+}
+
+pub fn series_won(series: &Series) -> Event {
+    let score: Vec<String> = series.wins.iter().map(|w| w.to_string()).collect();
+    Event::new("series_won")
+        .add_attribute("series_id", series.series_id.to_string())
+        .add_attribute(
+            "winner",
+            series
+                .winner
+                .as_ref()
+                .map(|w| w.to_string())
+                .unwrap_or_default(),
+        )
+        .add_attribute("score", score.join("-"))
+        .add_attribute("pot", series.pot.to_string())
+}
+
+pub fn series_cancelled(series: &Series) -> Event {
+    Event::new("series_cancelled").add_attribute("series_id", series.series_id.to_string())
+}
+
+pub fn stake_refunded(game_id: u64, recipient: &Addr, amount: &Coin) -> Event {
+    Event::new("stake_refunded")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+pub fn prize_paid(game_id: u64, recipient: &Addr, amount: &Coin) -> Event {
+    Event::new("prize_paid")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+pub fn game_cancelled(game: &Game) -> Event {
+    Event::new("game_cancelled")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("host", game.host.to_string())
+}
+
+pub fn tournament_created(tournament: &Tournament) -> Event {
+    Event::new("tournament_created")
+        .add_attribute("tournament_id", tournament.tournament_id.to_string())
+        .add_attribute("creator", tournament.creator.to_string())
+        .add_attribute("entry_fee", tournament.entry_fee.to_string())
+        .add_attribute("bracket_size", tournament.bracket_size.to_string())
+}
+
+pub fn tournament_registered(tournament: &Tournament, player: &Addr) -> Event {
+    Event::new("tournament_registered")
+        .add_attribute("tournament_id", tournament.tournament_id.to_string())
+        .add_attribute("player", player.to_string())
+}
+
+pub fn tournament_round_started(tournament: &Tournament) -> Event {
+    let game_ids: Vec<String> = tournament.games.iter().map(|id| id.to_string()).collect();
+    Event::new("tournament_round_started")
+        .add_attribute("tournament_id", tournament.tournament_id.to_string())
+        .add_attribute("round", tournament.round.to_string())
+        .add_attribute("game_ids", game_ids.join(","))
+}
+
+pub fn tournament_completed(tournament: &Tournament) -> Event {
+    Event::new("tournament_completed")
+        .add_attribute("tournament_id", tournament.tournament_id.to_string())
+        .add_attribute("champion", tournament.bracket[0].to_string())
+}
+
+pub fn tournament_prize_paid(tournament: &Tournament, recipient: &Addr, amount: &Coin) -> Event {
+    Event::new("tournament_prize_paid")
+        .add_attribute("tournament_id", tournament.tournament_id.to_string())
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+pub fn tournament_cancelled(tournament: &Tournament) -> Event {
+    Event::new("tournament_cancelled")
+        .add_attribute("tournament_id", tournament.tournament_id.to_string())
+}
+
+pub fn leaderboard_season_started(season: &LeaderboardSeason) -> Event {
+    let reward_pool: Vec<String> = season.reward_pool.iter().map(|c| c.to_string()).collect();
+    Event::new("leaderboard_season_started")
+        .add_attribute("season_id", season.season_id.to_string())
+        .add_attribute("reward_pool", reward_pool.join(","))
+}
+
+pub fn leaderboard_season_ended(season: &LeaderboardSeason) -> Event {
+    let top: Vec<String> = season
+        .results
+        .iter()
+        .map(|r| r.player.to_string())
+        .collect();
+    Event::new("leaderboard_season_ended")
+        .add_attribute("season_id", season.season_id.to_string())
+        .add_attribute("top", top.join(","))
+}
+
+pub fn season_reward_paid(season: &LeaderboardSeason, result: &SeasonResult) -> Event {
+    let reward: Vec<String> = result.reward.iter().map(|c| c.to_string()).collect();
+    Event::new("season_reward_paid")
+        .add_attribute("season_id", season.season_id.to_string())
+        .add_attribute("recipient", result.player.to_string())
+        .add_attribute("amount", reward.join(","))
+}
+
+pub fn league_configured(league: &League) -> Event {
+    Event::new("league_configured")
+        .add_attribute("season_duration", league.season_duration.to_string())
+        .add_attribute("dimension", league.rules.dimension.to_string())
+        .add_attribute("threshold", league.rules.threshold.to_string())
+        .add_attribute("win_points", league.points.win.to_string())
+        .add_attribute("draw_points", league.points.draw.to_string())
+        .add_attribute("loss_points", league.points.loss.to_string())
+}
+
+pub fn league_season_joined(season: &LeagueSeason, player: &Addr) -> Event {
+    Event::new("league_season_joined")
+        .add_attribute("season_id", season.season_id.to_string())
+        .add_attribute("player", player.to_string())
+}
+
+pub fn league_season_started(season: &LeagueSeason) -> Event {
+    let game_ids: Vec<String> = season.games.iter().map(|id| id.to_string()).collect();
+    Event::new("league_season_started")
+        .add_attribute("season_id", season.season_id.to_string())
+        .add_attribute("end", season.end.seconds().to_string())
+        .add_attribute("game_ids", game_ids.join(","))
+}
+
+pub fn league_season_finished(season: &LeagueSeason) -> Event {
+    Event::new("league_season_finished").add_attribute("season_id", season.season_id.to_string())
+}
+
+pub fn fees_distributed(name: &str, recipient: &Addr, amount: &[Coin]) -> Event {
+    let amount: Vec<String> = amount.iter().map(|coin| coin.to_string()).collect();
+    Event::new("fees_distributed")
+        .add_attribute("name", name)
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("amount", amount.join(","))
+}
+
+pub fn fee_caps_updated(denom: &str, caps: &FeeCaps) -> Event {
+    let bound = |cap: Option<Uint128>| cap.map_or_else(String::new, |amount| amount.to_string());
+    Event::new("fee_caps_updated")
+        .add_attribute("denom", denom)
+        .add_attribute("min_fee", bound(caps.min_fee))
+        .add_attribute("max_fee", bound(caps.max_fee))
+}
+
+pub fn config_updated(config: &Config) -> Event {
+    Event::new("config_updated")
+        .add_attribute("dimension", config.dimension.to_string())
+        .add_attribute("threshold", config.threshold.to_string())
+        .add_attribute("fee_bps", config.fee_bps.to_string())
+        .add_attribute("max_fee_bps", config.max_fee_bps.to_string())
+        .add_attribute(
+            "referral_percentage",
+            config.referral_percentage.to_string(),
+        )
+        .add_attribute("jackpot_bps", config.jackpot_bps.to_string())
+        .add_attribute("jackpot_odds", config.jackpot_odds.to_string())
+        .add_attribute("bets_close_after", config.bets_close_after.to_string())
+        .add_attribute("house_exposure_bps", config.house_exposure_bps.to_string())
+        .add_attribute(
+            "house_max_stake_bps",
+            config.house_max_stake_bps.to_string(),
+        )
+        .add_attribute("house_edge_bps", config.house_edge_bps.to_string())
+}
+
+pub fn session_key_authorized(key: &Addr, session: &Session) -> Event {
+    let game_id = session
+        .game_id
+        .map_or_else(String::new, |id| id.to_string());
+    let expires_at = session
+        .expires_at
+        .map_or_else(String::new, |time| time.seconds().to_string());
+    Event::new("session_key_authorized")
+        .add_attribute("key", key.to_string())
+        .add_attribute("player", session.player.to_string())
+        .add_attribute("game_id", game_id)
+        .add_attribute("expires_at", expires_at)
+}
+
+pub fn session_key_revoked(key: &Addr, player: &Addr) -> Event {
+    Event::new("session_key_revoked")
+        .add_attribute("key", key.to_string())
+        .add_attribute("player", player.to_string())
+}
+
+pub fn move_key_registered(player: &Addr) -> Event {
+    Event::new("move_key_registered").add_attribute("player", player.to_string())
+}
+
+pub fn signed_moves_submitted(game: &Game, submitter: &Addr, count: usize) -> Event {
+    Event::new("signed_moves_submitted")
+        .add_attribute("game_id", game.game_id.to_string())
+        .add_attribute("submitter", submitter.to_string())
+        .add_attribute("count", count.to_string())
+}
+
+pub fn equivocation_reported(game_id: u64, player: &Addr, move_no: u32) -> Event {
+    Event::new("equivocation_reported")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attribute("player", player.to_string())
+        .add_attribute("move_no", move_no.to_string())
+}
+
+pub fn house_deposited(provider: &Addr, amount: &Coin, shares: Uint128) -> Event {
+    Event::new("house_deposited")
+        .add_attribute("provider", provider.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("shares", shares.to_string())
+}
+
+pub fn house_withdrawn(provider: &Addr, amount: &Coin, shares: Uint128) -> Event {
+    Event::new("house_withdrawn")
+        .add_attribute("provider", provider.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("shares", shares.to_string())
+}
+
+pub fn bet_placed(game_id: u64, bettor: &Addr, outcome: BetOutcome, amount: &Coin) -> Event {
+    Event::new("bet_placed")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attribute("bettor", bettor.to_string())
+        .add_attribute("outcome", bet_outcome(outcome))
+        .add_attribute("amount", amount.to_string())
+}
+
+pub fn bet_claimed(game_id: u64, bettor: &Addr, amount: &Coin) -> Event {
+    Event::new("bet_claimed")
+        .add_attribute("game_id", game_id.to_string())
+        .add_attribute("bettor", bettor.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+fn rule_set(rule_set: RuleSet) -> &'static str {
+    match rule_set {
+        RuleSet::Classic => "classic",
+        RuleSet::Gomoku => "gomoku",
+        RuleSet::ConnectFour => "connect_four",
+        RuleSet::Misere => "misere",
+        RuleSet::Ultimate => "ultimate",
+    }
+}
+
+fn bet_outcome(outcome: BetOutcome) -> &'static str {
+    match outcome {
+        BetOutcome::Host => "host",
+        BetOutcome::Opponent => "opponent",
+        BetOutcome::Draw => "draw",
+    }
+}
+
+fn offer_kind(kind: &OfferKind) -> &'static str {
+    match kind {
+        OfferKind::Draw => "draw",
+        OfferKind::Takeback => "takeback",
+        OfferKind::Rematch => "rematch",
+    }
+}
@@ -0,0 +1,182 @@
+use cosmwasm_std::{Coin, StdResult, Storage, Uint128};
+
+use crate::state::{Config, FeeCaps, FeeShare, FEE_CAPS, FEE_POOL};
+use crate::utils::add_coin;
+
+/// Weights of the fee distribution table are expressed in basis points of this total.
+pub const TOTAL_WEIGHT: u16 = 10_000;
+
+/// Fee charged on `stake`: the basis points of the matching tier, clamped to the caps of the
+/// stake denom. A minimum fee can exceed small stakes, see `assert_stake_covers_fee`.
+pub fn compute_fee(config: &Config, caps: &FeeCaps, stake: Uint128) -> Uint128 {
+    let fee_bps = config
+        .fee_tiers
+        .iter()
+        .filter(|tier| tier.min_stake <= stake)
+        .max_by_key(|tier| tier.min_stake)
+        .map_or(config.fee_bps, |tier| tier.fee_bps);
+    let mut fee = stake.multiply_ratio(u128::from(fee_bps), u128::from(TOTAL_WEIGHT));
+    if let Some(min_fee) = caps.min_fee {
+        fee = fee.max(min_fee);
+    }
+    if let Some(max_fee) = caps.max_fee {
+        fee = fee.min(max_fee);
+    }
+
+    fee
+}
+
+/// Fee charged on `stake` with the caps stored for its denom.
+pub fn fee_for(storage: &dyn Storage, config: &Config, stake: &Coin) -> StdResult<Coin> {
+    let caps = FEE_CAPS
+        .may_load(storage, &stake.denom)?
+        .unwrap_or_default();
+
+    Ok(Coin {
+        denom: stake.denom.clone(),
+        amount: compute_fee(config, &caps, stake.amount),
+    })
+}
+
+/// Adds `fee` to the pool waiting for the next distribution.
+pub fn accrue_fee(storage: &mut dyn Storage, fee: &Coin) -> StdResult<()> {
+    if fee.amount.is_zero() {
+        return Ok(());
+    }
+    FEE_POOL.update(storage, |mut pool| -> StdResult<_> {
+        add_coin(&mut pool, fee);
+        Ok(pool)
+    })?;
+
+    Ok(())
+}
+
+/// Splits `pool` between `shares` by weight. Rounding dust of every denom goes to the last share
+/// so the whole pool is always handed out.
+pub fn split_fees(pool: &[Coin], shares: &[FeeShare]) -> Vec<Vec<Coin>> {
+    let mut amounts: Vec<Vec<Coin>> = vec![Vec::new(); shares.len()];
+    for coin in pool {
+        let mut remaining = coin.amount;
+        for (i, share) in shares.iter().enumerate() {
+            let amount = if i + 1 == shares.len() {
+                remaining
+            } else {
+                coin.amount
+                    .multiply_ratio(u128::from(share.weight), u128::from(TOTAL_WEIGHT))
+            };
+            remaining -= amount;
+            if amount != Uint128::zero() {
+                amounts[i].push(Coin {
+                    denom: coin.denom.clone(),
+                    amount,
+                });
+            }
+        }
+    }
+
+    amounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::FeeTier;
+    use crate::testing;
+    use cosmwasm_std::{coin, Addr};
+
+    fn mock_config(fee_tiers: Vec<FeeTier>) -> Config {
+        Config {
+            fee_tiers,
+            ..testing::mock_config()
+        }
+    }
+
+    #[test]
+    fn compute_fee_from_tiers() {
+        let config = mock_config(vec![
+            FeeTier {
+                min_stake: Uint128::new(1_000_000),
+                fee_bps: 50,
+            },
+            FeeTier {
+                min_stake: Uint128::new(100_000),
+                fee_bps: 100,
+            },
+        ]);
+        let caps = FeeCaps::default();
+
+        assert_eq!(
+            Uint128::new(1_000),
+            compute_fee(&config, &caps, Uint128::new(50_000))
+        );
+        assert_eq!(
+            Uint128::new(1_000),
+            compute_fee(&config, &caps, Uint128::new(100_000))
+        );
+        assert_eq!(
+            Uint128::new(5_000),
+            compute_fee(&config, &caps, Uint128::new(1_000_000))
+        );
+    }
+
+    #[test]
+    fn compute_fee_within_caps() {
+        let config = mock_config(vec![]);
+        let caps = FeeCaps {
+            min_fee: Some(Uint128::new(100)),
+            max_fee: Some(Uint128::new(1_000)),
+        };
+
+        assert_eq!(
+            Uint128::new(100),
+            compute_fee(&config, &caps, Uint128::new(1_000))
+        );
+        assert_eq!(
+            Uint128::new(400),
+            compute_fee(&config, &caps, Uint128::new(20_000))
+        );
+        assert_eq!(
+            Uint128::new(1_000),
+            compute_fee(&config, &caps, Uint128::new(1_000_000))
+        );
+        assert_eq!(
+            Uint128::new(100),
+            compute_fee(&config, &caps, Uint128::new(50))
+        );
+    }
+
+    fn share(name: &str, weight: u16) -> FeeShare {
+        FeeShare {
+            name: name.to_string(),
+            address: Addr::unchecked(name),
+            weight,
+        }
+    }
+
+    #[test]
+    fn split_fees_by_weight() {
+        let shares = vec![
+            share("treasury", 5_000),
+            share("dev_fund", 3_000),
+            share("jackpot", 1_999),
+            share("burn", 1),
+        ];
+        let pool = vec![coin(1_000_001, "uluna"), coin(10, "uust")];
+
+        assert_eq!(
+            vec![
+                vec![coin(500_000, "uluna"), coin(5, "uust")],
+                vec![coin(300_000, "uluna"), coin(3, "uust")],
+                vec![coin(199_900, "uluna"), coin(1, "uust")],
+                vec![coin(101, "uluna"), coin(1, "uust")],
+            ],
+            split_fees(&pool, &shares)
+        );
+    }
+
+    #[test]
+    fn split_empty_pool() {
+        let shares = vec![share("treasury", 10_000)];
+        assert_eq!(vec![Vec::<Coin>::new()], split_fees(&[], &shares));
+    }
+}
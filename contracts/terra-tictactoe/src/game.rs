@@ -1,82 +1,527 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary};
 
-use crate::state::Game;
+use crate::state::{BoardRules, Game, Move, RuleSet, UltimateBoard};
+use crate::ultimate::new_board;
+use crate::utils::generate_random_u8;
 use crate::ContractError;
 
+/// Marks in a line that win a game of gomoku.
+pub const GOMOKU_LINE: u8 = 5;
+
+/// Adjusts the board `rules` to `rule_set`, gomoku is always played for five in a line.
+pub fn variant_rules(rule_set: RuleSet, rules: BoardRules) -> BoardRules {
+    match rule_set {
+        RuleSet::Gomoku => BoardRules {
+            dimension: rules.dimension.max(GOMOKU_LINE),
+            threshold: GOMOKU_LINE,
+        },
+        RuleSet::Ultimate => BoardRules {
+            dimension: 9,
+            threshold: 3,
+        },
+        _ => rules,
+    }
+}
+
+/// Sets up the board of a new game played by `rule_set`, ultimate tic-tac-toe leaves the
+/// grid empty.
+pub fn new_boards(
+    rule_set: RuleSet,
+    randomness: &Binary,
+    rules: &BoardRules,
+) -> (Vec<Vec<i8>>, Option<UltimateBoard>) {
+    match rule_set {
+        RuleSet::Ultimate => (vec![], Some(new_board())),
+        _ => (new_grid(randomness, rules), None),
+    }
+}
+
+/// Sets up an empty board for `rules` with `rules.threshold` cells disabled at random.
+pub fn new_grid(randomness: &Binary, rules: &BoardRules) -> Vec<Vec<i8>> {
+    let mut grid: Vec<Vec<i8>> = Vec::new();
+    let mut disabled_cells: Vec<(u8, u8)> = Vec::new();
+    let mut round: usize = 0;
+
+    for _ in 0..rules.threshold {
+        let x = generate_random_u8(randomness, round, rules.dimension);
+        round += 1;
+        let y = generate_random_u8(randomness, round, rules.dimension);
+        round += 1;
+        disabled_cells.push((x, y));
+    }
+
+    for i in 0..rules.dimension {
+        let mut row: Vec<i8> = Vec::new();
+        for j in 0..rules.dimension {
+            if disabled_cells.contains(&(i, j)) {
+                row.push(-1)
+            } else {
+                row.push(0);
+            }
+        }
+        grid.push(row);
+    }
+
+    grid
+}
+
+/// Cell a move on `(x, y)` of `game` lands on. In connect four the mark drops down column
+/// `y` to the lowest empty cell, disabled cells stop it like marks do.
+pub fn target_cell(game: &Game, x: u8, y: u8) -> Result<(usize, usize), ContractError> {
+    let (pos_x, pos_y) = (usize::from(x), usize::from(y));
+    let dimension = game.grid.len();
+    if pos_x >= dimension || pos_y >= dimension {
+        return Err(ContractError::MoveNotAllow {});
+    }
+    if game.rule_set != RuleSet::ConnectFour {
+        return Ok((pos_x, pos_y));
+    }
+    (0..dimension)
+        .take_while(|row| game.grid[*row][pos_y] == 0)
+        .last()
+        .map(|row| (row, pos_y))
+        .ok_or(ContractError::MoveNotAllow {})
+}
+
+/// Marks of the seats of a game in turn order, the first two are the marks of the host and
+/// the opponent of two player games.
+pub const SEAT_MARKS: [i8; MAX_SEATS as usize] = [1, 100, 2, 3];
+
+/// Most players a game can seat.
+pub const MAX_SEATS: u8 = 4;
+
+/// Most teammates that can share a seat with the player holding it.
+pub const MAX_TEAMMATES: usize = 3;
+
+/// Player holding the seat `member` plays for, either as that player or as a teammate.
+pub fn seat_holder(game: &Game, member: &Addr) -> Option<Addr> {
+    game.players
+        .iter()
+        .enumerate()
+        .find(|(seat, player)| {
+            *player == member
+                || game
+                    .teams
+                    .get(*seat)
+                    .map_or(false, |team| team.contains(member))
+        })
+        .map(|(_, player)| player.clone())
+}
+
+/// Mark of the seat of `player` in `game`.
+pub fn seat_mark(game: &Game, player: &Addr) -> Option<i8> {
+    game.players
+        .iter()
+        .position(|p| p == player)
+        .map(|seat| SEAT_MARKS[seat])
+}
+
 pub fn get_mark_for_cell(game: &Game, x: usize, y: usize) -> Result<i8, ContractError> {
-    if game.grid[x][y] != 0 || game.grid[x][y] == -1 {
+    if game.grid[x][y] != 0 {
         return Err(ContractError::MoveNotAllow {});
-    } else if game.host.to_string() == game.next_player {
-        Ok(1)
-    } else {
-        Ok(100)
     }
+    seat_mark(game, &game.next_player).ok_or(ContractError::Unauthorized {})
 }
 
+/// Player of the seat after the one to move, seats take turns in the order they were taken
+/// and the seats of resigned players are skipped.
 pub fn get_next_player(game: &Game) -> Result<Addr, ContractError> {
-    if game.next_player == game.host {
+    let seat = game
+        .players
+        .iter()
+        .position(|p| *p == game.next_player)
+        .ok_or(ContractError::Unauthorized {})?;
+    let seats = game.players.len();
+    (1..=seats)
+        .map(|step| &game.players[(seat + step) % seats])
+        .find(|player| !game.resigned.contains(player))
+        .cloned()
+        .ok_or(ContractError::Unauthorized {})
+}
+
+/// Players still playing `game`, in turn order.
+pub fn active_players(game: &Game) -> Vec<Addr> {
+    game.players
+        .iter()
+        .filter(|player| !game.resigned.contains(player))
+        .cloned()
+        .collect()
+}
+
+/// The other player of a two player game.
+pub fn get_opponent_of(game: &Game, player: &Addr) -> Result<Addr, ContractError> {
+    if game.players.len() > 2 {
+        Err(ContractError::TwoPlayersOnly {})
+    } else if *player == game.host {
         Ok(game.opponent.clone())
-    } else {
+    } else if *player == game.opponent {
         Ok(game.host.clone())
+    } else {
+        Err(ContractError::Unauthorized {})
+    }
+}
+
+/// Number of marks in `cells` when they all belong to the same player, disabled cells do not
+/// count.
+fn single_owner_count(cells: impl Iterator<Item = i8>) -> Option<u16> {
+    let mut owner = None;
+    let mut count = 0;
+    for mark in cells.filter(|mark| *mark > 0) {
+        match owner {
+            Some(owner) if owner != mark => return None,
+            _ => owner = Some(mark),
+        }
+        count += 1;
     }
+    Some(count)
 }
 
+/// Whether a full row, column or diagonal of the board holds exactly `threshold` marks, all
+/// of the same player.
 pub fn is_game_completed(
     game: &Game,
     dimension: u16,
     threshold: u16,
 ) -> Result<bool, ContractError> {
-    let mut sum_vertically: u16 = 0;
-    let mut sum_horizontally: u16 = 0;
-    let mut sum_diagonally_x: u16 = 0;
-    let mut sum_diagonally_y: u16 = 0;
-    let opponent_threshold = 100u16 * threshold;
-
-    for x in 0..dimension {
-        let pos_x = usize::from(x);
-        for y in 0..dimension {
-            let pos_y = usize::from(y);
-            sum_vertically += if game.grid[pos_x][pos_y].is_negative() {
-                0
-            } else {
-                game.grid[pos_x][pos_y] as u16
-            };
-            sum_horizontally += if game.grid[pos_y][pos_x].is_negative() {
-                0
-            } else {
-                game.grid[pos_y][pos_x] as u16
-            };
-        }
-        if (sum_vertically == threshold)
-            || (sum_vertically == opponent_threshold)
-            || (sum_horizontally == threshold)
-            || (sum_horizontally == opponent_threshold)
-        {
-            return Ok(true);
-        }
-        sum_vertically = 0;
-        sum_horizontally = 0;
-
-        let diag_y = usize::from(dimension - 1 - x);
-        sum_diagonally_x += if game.grid[pos_x][pos_x].is_negative() {
-            0
-        } else {
-            game.grid[pos_x][pos_x] as u16
-        };
-        sum_diagonally_y += if game.grid[diag_y][pos_x].is_negative() {
-            0
-        } else {
-            game.grid[diag_y][pos_x] as u16
-        };
-    }
-
-    if (sum_diagonally_x == threshold)
-        || (sum_diagonally_x == opponent_threshold)
-        || (sum_diagonally_y == threshold)
-        || (sum_diagonally_y == opponent_threshold)
+    let size = usize::from(dimension);
+    let grid = &game.grid;
+    let is_won = |count: Option<u16>| count == Some(threshold);
+
+    if grid
+        .iter()
+        .any(|row| is_won(single_owner_count(row.iter().copied())))
+        || (0..size).any(|i| is_won(single_owner_count(grid.iter().map(|row| row[i]))))
     {
         return Ok(true);
     }
 
-    Ok(false)
+    Ok(is_won(single_owner_count((0..size).map(|i| grid[i][i])))
+        || is_won(single_owner_count((0..size).map(|i| grid[size - 1 - i][i]))))
+}
+
+/// Length of the longest line of equal marks through the cell `(x, y)`.
+fn line_through(grid: &[Vec<i8>], x: usize, y: usize) -> usize {
+    let mark = grid[x][y];
+    let count = |dx: isize, dy: isize| {
+        let mut length = 0;
+        let (mut i, mut j) = (x as isize + dx, y as isize + dy);
+        while i >= 0
+            && j >= 0
+            && grid.get(i as usize).and_then(|row| row.get(j as usize)) == Some(&mark)
+        {
+            length += 1;
+            i += dx;
+            j += dy;
+        }
+        length
+    };
+    [(1, 0), (0, 1), (1, 1), (1, -1)]
+        .iter()
+        .map(|(dx, dy)| 1 + count(*dx, *dy) + count(-dx, -dy))
+        .max()
+        .unwrap_or(1)
+}
+
+/// Winner of `game` once the player to move marked `(x, y)`, if the move ended it.
+pub fn winner_after_move(game: &Game, x: usize, y: usize) -> Result<Option<Addr>, ContractError> {
+    let rules = game.rules;
+    let threshold = usize::from(rules.threshold);
+    let completed = match game.rule_set {
+        RuleSet::Classic | RuleSet::Misere => {
+            is_game_completed(game, rules.dimension as u16, rules.threshold as u16)?
+        }
+        RuleSet::Gomoku => line_through(&game.grid, x, y) == threshold,
+        RuleSet::ConnectFour => line_through(&game.grid, x, y) >= threshold,
+        // Played on its own board
+        RuleSet::Ultimate => false,
+    };
+
+    match (completed, game.rule_set) {
+        (false, _) => Ok(None),
+        (true, RuleSet::Misere) => Ok(Some(get_next_player(game)?)),
+        (true, _) => Ok(Some(game.next_player.clone())),
+    }
+}
+
+/// Reply of the house, which holds the seat to move on `game`, or `None` on a full board. It
+/// looks two moves ahead: a winning move is taken, a winning move of the opponent blocked
+/// and a move losing a misère game only made when nothing else is left. The other moves
+/// rank by the line of the house they extend and by how close to the center they are.
+pub fn house_move(game: &Game) -> Result<Option<(u8, u8)>, ContractError> {
+    let house = game.next_player.clone();
+    let rival = get_opponent_of(game, &house)?;
+    let mut best: Option<(i32, (usize, usize))> = None;
+    for (x, y) in open_cells(game) {
+        let score = score_move(game, x, y, &house, &rival)?;
+        if best.map_or(true, |(top, _)| score > top) {
+            best = Some((score, (x, y)));
+        }
+    }
+    Ok(best.map(|(_, (x, y))| (x as u8, y as u8)))
+}
+
+/// Cells a mark can land on, in connect four only the lowest empty cell of each column.
+fn open_cells(game: &Game) -> Vec<(usize, usize)> {
+    let dimension = game.grid.len();
+    if game.rule_set == RuleSet::ConnectFour {
+        return (0..dimension as u8)
+            .filter_map(|y| target_cell(game, 0, y).ok())
+            .collect();
+    }
+    (0..dimension)
+        .flat_map(|x| (0..dimension).map(move |y| (x, y)))
+        .filter(|(x, y)| game.grid[*x][*y] == 0)
+        .collect()
+}
+
+/// Whether no mark can be placed on the grid of `game` anymore.
+pub fn is_board_full(game: &Game) -> bool {
+    open_cells(game).is_empty()
+}
+
+/// Winner of `game` after `player` marked `(x, y)` and the length of the line it extends.
+fn mark_cell(
+    game: &Game,
+    x: usize,
+    y: usize,
+    player: &Addr,
+) -> Result<(Option<Addr>, usize), ContractError> {
+    let mut next = game.clone();
+    next.grid[x][y] = seat_mark(game, player).ok_or(ContractError::Unauthorized {})?;
+    next.next_player = player.clone();
+    Ok((
+        winner_after_move(&next, x, y)?,
+        line_through(&next.grid, x, y),
+    ))
+}
+
+fn score_move(
+    game: &Game,
+    x: usize,
+    y: usize,
+    house: &Addr,
+    rival: &Addr,
+) -> Result<i32, ContractError> {
+    let (winner, line) = mark_cell(game, x, y, house)?;
+    if winner.as_ref() == Some(house) {
+        return Ok(i32::MAX);
+    } else if winner.as_ref() == Some(rival) {
+        return Ok(i32::MIN);
+    }
+    let (rival_winner, _) = mark_cell(game, x, y, rival)?;
+    let block = if rival_winner.as_ref() == Some(rival) {
+        1_000
+    } else {
+        0
+    };
+    let line = if game.rule_set == RuleSet::Misere {
+        -(line as i32)
+    } else {
+        line as i32
+    };
+    let center = game.grid.len() as i32 - 1;
+    let distance = (2 * x as i32 - center).abs() + (2 * y as i32 - center).abs();
+
+    Ok(block + 10 * line - distance)
+}
+
+/// Rebuilds the board of `game` as it looked after the first `upto` entries of `moves`.
+/// Disabled cells are kept, every mark is cleared and the moves are applied in order.
+pub fn replay(game: &Game, moves: &[Move], upto: usize) -> Result<Vec<Vec<i8>>, ContractError> {
+    let mut grid: Vec<Vec<i8>> = game
+        .grid
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| if *cell < 0 { *cell } else { 0 })
+                .collect()
+        })
+        .collect();
+
+    for mv in moves.iter().take(upto) {
+        let pos_x = usize::from(mv.x);
+        let pos_y = usize::from(mv.y);
+        match grid.get(pos_x).and_then(|row| row.get(pos_y)) {
+            Some(0) => {}
+            _ => return Err(ContractError::MoveNotAllow {}),
+        }
+        grid[pos_x][pos_y] = seat_holder(game, &mv.player)
+            .and_then(|player| seat_mark(game, &player))
+            .ok_or(ContractError::MoveNotAllow {})?;
+    }
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use cosmwasm_std::{coin, Timestamp};
+
+    fn mock_game() -> Game {
+        Game {
+            bet: coin(100, "uust"),
+            grid: vec![vec![1, 100, -1], vec![0, 1, 0], vec![100, 0, 0]],
+            next_player: Addr::unchecked("opponent"),
+            updated_at: Timestamp::from_seconds(0),
+            move_count: 4,
+            rules: BoardRules {
+                dimension: 3,
+                threshold: 3,
+            },
+            ..testing::mock_game()
+        }
+    }
+
+    #[test]
+    fn house_wins_before_it_blocks() {
+        let mut game = mock_game();
+        // The host threatens the diagonal
+        assert_eq!(Some((2, 2)), house_move(&game).unwrap());
+
+        // Both threaten a line, the house completes its own
+        game.grid = vec![vec![1, 100, -1], vec![0, 100, 0], vec![1, 0, 0]];
+        assert_eq!(Some((2, 1)), house_move(&game).unwrap());
+
+        // Completing a line of a misère game loses
+        game.rule_set = RuleSet::Misere;
+        assert_ne!(Some((2, 1)), house_move(&game).unwrap());
+
+        game.grid = vec![vec![1, 100, -1], vec![100, 1, 1], vec![1, 100, 100]];
+        assert_eq!(None, house_move(&game).unwrap());
+    }
+
+    #[test]
+    fn house_drops_into_columns() {
+        let mut game = mock_game();
+        game.rule_set = RuleSet::ConnectFour;
+        game.grid = vec![vec![0, 0, 0], vec![0, 0, 0], vec![1, 1, 0]];
+        assert_eq!(Some((2, 2)), house_move(&game).unwrap());
+        game.grid = vec![vec![1, 100, 0], vec![1, 100, 0], vec![100, 1, 0]];
+        assert_eq!(Some((2, 2)), house_move(&game).unwrap());
+    }
+
+    fn mock_move(move_no: u32, player: &str, x: u8, y: u8) -> Move {
+        Move {
+            move_no,
+            player: Addr::unchecked(player),
+            x,
+            y,
+            time: Timestamp::from_seconds(u64::from(move_no)),
+            height: u64::from(move_no),
+        }
+    }
+
+    #[test]
+    fn replay_reconstructs_intermediate_boards() {
+        let game = mock_game();
+        let moves = vec![
+            mock_move(1, "host", 0, 0),
+            mock_move(2, "opponent", 0, 1),
+            mock_move(3, "host", 1, 1),
+            mock_move(4, "opponent", 2, 0),
+        ];
+
+        let empty = replay(&game, &moves, 0).unwrap();
+        assert_eq!(vec![vec![0, 0, -1], vec![0, 0, 0], vec![0, 0, 0]], empty);
+
+        let after_two = replay(&game, &moves, 2).unwrap();
+        assert_eq!(
+            vec![vec![1, 100, -1], vec![0, 0, 0], vec![0, 0, 0]],
+            after_two
+        );
+
+        let last = replay(&game, &moves, moves.len()).unwrap();
+        assert_eq!(game.grid, last);
+    }
+
+    #[test]
+    fn replay_rejects_invalid_history() {
+        let game = mock_game();
+
+        let disabled_cell = vec![mock_move(1, "host", 0, 2)];
+        assert!(replay(&game, &disabled_cell, 1).is_err());
+
+        let occupied_cell = vec![mock_move(1, "host", 1, 1), mock_move(2, "opponent", 1, 1)];
+        assert!(replay(&game, &occupied_cell, 2).is_err());
+
+        let out_of_bounds = vec![mock_move(1, "host", 3, 0)];
+        assert!(replay(&game, &out_of_bounds, 1).is_err());
+    }
+
+    fn empty_game(rule_set: RuleSet, dimension: u8, threshold: u8) -> Game {
+        let mut game = mock_game();
+        game.grid = vec![vec![0; usize::from(dimension)]; usize::from(dimension)];
+        game.next_player = game.host.clone();
+        game.rule_set = rule_set;
+        game.rules = variant_rules(
+            rule_set,
+            BoardRules {
+                dimension,
+                threshold,
+            },
+        );
+        game
+    }
+
+    #[test]
+    fn gomoku_needs_exactly_five() {
+        let mut game = empty_game(RuleSet::Gomoku, 4, 3);
+        assert_eq!(
+            BoardRules {
+                dimension: 5,
+                threshold: 5
+            },
+            game.rules
+        );
+        game = empty_game(RuleSet::Gomoku, 7, 4);
+        for &y in [0, 1, 3, 4].iter() {
+            game.grid[0][y] = 1;
+        }
+        assert_eq!(None, winner_after_move(&game, 0, 4).unwrap());
+        game.grid[0][2] = 1;
+        assert_eq!(
+            Some(game.host.clone()),
+            winner_after_move(&game, 0, 2).unwrap()
+        );
+        // An overline does not count
+        game.grid[0][5] = 1;
+        assert_eq!(None, winner_after_move(&game, 0, 5).unwrap());
+    }
+
+    #[test]
+    fn connect_four_marks_drop_down_columns() {
+        let mut game = empty_game(RuleSet::ConnectFour, 4, 3);
+        game.grid[3][0] = 100;
+        game.grid[0][2] = -1;
+        assert_eq!((2, 0), target_cell(&game, 0, 0).unwrap());
+        assert_eq!((3, 1), target_cell(&game, 1, 1).unwrap());
+        assert!(target_cell(&game, 0, 2).is_err());
+        assert!(target_cell(&game, 0, 4).is_err());
+
+        game.grid[1][3] = 1;
+        game.grid[2][3] = 1;
+        game.grid[3][3] = 1;
+        assert_eq!(
+            Some(game.host.clone()),
+            winner_after_move(&game, 1, 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn misere_line_loses() {
+        let mut game = mock_game();
+        game.next_player = game.host.clone();
+        game.grid[2][2] = 1;
+        assert_eq!(
+            Some(game.host.clone()),
+            winner_after_move(&game, 2, 2).unwrap()
+        );
+        game.rule_set = RuleSet::Misere;
+        assert_eq!(
+            Some(game.opponent.clone()),
+            winner_after_move(&game, 2, 2).unwrap()
+        );
+    }
 }
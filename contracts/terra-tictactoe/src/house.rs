@@ -0,0 +1,244 @@
+use cosmwasm_std::Uint128;
+
+use crate::fees::TOTAL_WEIGHT;
+use crate::state::{Config, Game, GameState, House};
+use crate::ContractError;
+
+/// Everything the house holds, its stakes in running games counted at face value.
+pub fn pool_value(house: &House) -> Uint128 {
+    house.bankroll + house.exposure
+}
+
+/// Value of the pool to a provider joining it. The stakes of the house in running games count
+/// as won, twice the stake, so a deposit never buys into a win that is about to settle.
+fn mint_value(house: &House) -> Uint128 {
+    house.bankroll + house.exposure + house.exposure
+}
+
+/// Value of the pool to a provider leaving it. The stakes of the house in running games count
+/// as lost, so a withdrawal never leaves a loss that is about to settle to the others.
+fn redeem_value(house: &House) -> Uint128 {
+    house.bankroll
+}
+
+/// Share of everything the house holds given by `bps`.
+fn pool_share(house: &House, bps: u16) -> Uint128 {
+    pool_value(house).multiply_ratio(u128::from(bps), u128::from(TOTAL_WEIGHT))
+}
+
+/// Most the house may have at stake in running games, a share of everything it holds.
+pub fn max_exposure(house: &House, exposure_bps: u16) -> Uint128 {
+    pool_share(house, exposure_bps)
+}
+
+/// Moves `stake` out of the bankroll into a new game as long as neither the stake of the
+/// single game nor the exposure of the house exceed their configured share of the pool.
+pub fn reserve_stake(
+    house: &mut House,
+    stake: Uint128,
+    config: &Config,
+) -> Result<(), ContractError> {
+    if stake > house.bankroll
+        || stake > pool_share(house, config.house_max_stake_bps)
+        || house.exposure + stake > max_exposure(house, config.house_exposure_bps)
+    {
+        return Err(ContractError::HouseExposureExceeded {});
+    }
+    house.bankroll -= stake;
+    house.exposure += stake;
+    Ok(())
+}
+
+/// Share of the `fee` of a house game the house keeps for its liquidity providers.
+pub fn house_edge(config: &Config, fee: Uint128) -> Uint128 {
+    fee.multiply_ratio(u128::from(config.house_edge_bps), u128::from(TOTAL_WEIGHT))
+}
+
+/// Adds a deposit of `amount` to the bankroll and returns the shares minted for it. The
+/// first deposit into an empty pool gets a share per unit.
+pub fn deposit(house: &mut House, amount: Uint128) -> Uint128 {
+    let value = mint_value(house);
+    let shares = if house.shares.is_zero() || value.is_zero() {
+        amount
+    } else {
+        amount.multiply_ratio(house.shares, value)
+    };
+    house.bankroll += amount;
+    house.shares += shares;
+    shares
+}
+
+/// What `shares` of the pool are worth when withdrawn.
+pub fn share_value(house: &House, shares: Uint128) -> Uint128 {
+    if house.shares.is_zero() {
+        return Uint128::zero();
+    }
+    shares.multiply_ratio(redeem_value(house), house.shares)
+}
+
+/// Burns `shares` and takes their value out of the bankroll. What is left has to keep
+/// covering the stakes of the house in running games.
+pub fn withdraw(
+    house: &mut House,
+    shares: Uint128,
+    exposure_bps: u16,
+) -> Result<Uint128, ContractError> {
+    let amount = share_value(house, shares);
+    if amount > house.bankroll {
+        return Err(ContractError::InsufficientReserves {});
+    }
+    let mut rest = house.clone();
+    rest.bankroll -= amount;
+    rest.shares -= shares;
+    if rest.exposure > max_exposure(&rest, exposure_bps) {
+        return Err(ContractError::InsufficientReserves {});
+    }
+    *house = rest;
+    Ok(amount)
+}
+
+/// Releases the stake of the house from the finished `game` and takes back whatever the
+/// house won, the whole pot of a won game or its stake out of a draw.
+pub fn settle_stake(house: &mut House, game: &Game) {
+    let stake = game.bet.amount.multiply_ratio(1u128, 2u128);
+    house.exposure = house.exposure.checked_sub(stake).unwrap_or_default();
+    if game.state == GameState::Drawn {
+        house.bankroll += stake;
+    } else if game.winner == game.opponent {
+        house.bankroll += game.bet.amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use cosmwasm_std::{coin, Addr};
+
+    fn mock_config(house_exposure_bps: u16, house_max_stake_bps: u16) -> Config {
+        Config {
+            house_exposure_bps,
+            house_max_stake_bps,
+            ..testing::mock_config()
+        }
+    }
+
+    fn mock_game(state: GameState, winner: &str) -> Game {
+        Game {
+            bet: coin(200, "uust"),
+            state,
+            opponent: Addr::unchecked("house"),
+            winner: Addr::unchecked(winner),
+            move_count: 5,
+            players: vec![Addr::unchecked("host"), Addr::unchecked("house")],
+            vs_house: true,
+            ..testing::mock_game()
+        }
+    }
+
+    #[test]
+    fn exposure_is_capped() {
+        let config = mock_config(2_500, 2_000);
+        let mut house = House {
+            bankroll: Uint128::new(1_000),
+            exposure: Uint128::zero(),
+            shares: Uint128::new(1_000),
+        };
+        assert_eq!(Uint128::new(250), max_exposure(&house, 2_500));
+        match reserve_stake(&mut house, Uint128::new(201), &config) {
+            Err(ContractError::HouseExposureExceeded {}) => {}
+            _ => panic!("Must return HouseExposureExceeded error"),
+        }
+        reserve_stake(&mut house, Uint128::new(200), &config).unwrap();
+        assert_eq!(Uint128::new(800), house.bankroll);
+        assert_eq!(Uint128::new(200), house.exposure);
+        match reserve_stake(&mut house, Uint128::new(51), &config) {
+            Err(ContractError::HouseExposureExceeded {}) => {}
+            _ => panic!("Must return HouseExposureExceeded error"),
+        }
+        reserve_stake(&mut house, Uint128::new(50), &config).unwrap();
+    }
+
+    #[test]
+    fn shares_track_the_pool() {
+        let mut house = House::default();
+        assert_eq!(
+            Uint128::new(1_000),
+            deposit(&mut house, Uint128::new(1_000))
+        );
+        reserve_stake(&mut house, Uint128::new(100), &mock_config(5_000, 5_000)).unwrap();
+
+        // The house won its game, later deposits get fewer shares
+        settle_stake(&mut house, &mock_game(GameState::PriceWithdrawn, "house"));
+        assert_eq!(Uint128::new(1_100), pool_value(&house));
+        assert_eq!(
+            Uint128::new(1_000),
+            deposit(&mut house, Uint128::new(1_100))
+        );
+        assert_eq!(
+            Uint128::new(1_100),
+            share_value(&house, Uint128::new(1_000))
+        );
+
+        assert_eq!(
+            Uint128::new(1_100),
+            withdraw(&mut house, Uint128::new(1_000), 5_000).unwrap()
+        );
+        assert_eq!(Uint128::new(1_100), house.bankroll);
+        assert_eq!(Uint128::new(1_000), house.shares);
+    }
+
+    #[test]
+    fn open_stakes_never_favour_the_provider_moving() {
+        let mut house = House::default();
+        deposit(&mut house, Uint128::new(1_000));
+        reserve_stake(&mut house, Uint128::new(100), &mock_config(5_000, 5_000)).unwrap();
+
+        // Leaving counts the 100 at stake as lost, joining counts it as won
+        assert_eq!(Uint128::new(450), share_value(&house, Uint128::new(500)));
+        assert_eq!(
+            Uint128::new(1_000),
+            deposit(&mut house, Uint128::new(1_100))
+        );
+    }
+
+    #[test]
+    fn reserves_keep_covering_the_exposure() {
+        let mut house = House::default();
+        deposit(&mut house, Uint128::new(1_000));
+        reserve_stake(&mut house, Uint128::new(200), &mock_config(2_500, 2_500)).unwrap();
+
+        // A quarter of what is left after the withdrawal still has to cover the 200 at stake
+        match withdraw(&mut house, Uint128::new(300), 2_500) {
+            Err(ContractError::InsufficientReserves {}) => {}
+            _ => panic!("Must return InsufficientReserves error"),
+        }
+        assert_eq!(
+            Uint128::new(200),
+            withdraw(&mut house, Uint128::new(250), 2_500).unwrap()
+        );
+        match withdraw(&mut house, Uint128::new(10), 2_500) {
+            Err(ContractError::InsufficientReserves {}) => {}
+            _ => panic!("Must return InsufficientReserves error"),
+        }
+    }
+
+    #[test]
+    fn stakes_come_back_with_the_result() {
+        let stake = || House {
+            bankroll: Uint128::new(900),
+            exposure: Uint128::new(100),
+            shares: Uint128::new(1_000),
+        };
+        for (game, bankroll) in vec![
+            (mock_game(GameState::PriceWithdrawn, "house"), 1_100),
+            (mock_game(GameState::Drawn, ""), 1_000),
+            (mock_game(GameState::Completed, "host"), 900),
+        ] {
+            let mut house = stake();
+            settle_stake(&mut house, &game);
+            assert_eq!(Uint128::new(bankroll), house.bankroll);
+            assert!(house.exposure.is_zero());
+        }
+    }
+}
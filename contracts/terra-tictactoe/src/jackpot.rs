@@ -0,0 +1,154 @@
+use cosmwasm_std::{Binary, Coin, StdResult, Storage};
+use sha2::{Digest, Sha256};
+
+use crate::fees::TOTAL_WEIGHT;
+use crate::state::{Config, Game, JackpotDraw, JACKPOT};
+use crate::utils::add_coin;
+
+/// Terrand rounds between the winning move and the round drawing the jackpot. The round of a
+/// draw must not be out yet when the game is won, or the players could time their win on it.
+pub const DRAW_DELAY: u64 = 10;
+
+/// Moves the configured share of `fee` into the jackpot and returns what is left of the fee.
+pub fn fund_jackpot(storage: &mut dyn Storage, config: &Config, fee: &Coin) -> StdResult<Coin> {
+    let share = fee
+        .amount
+        .multiply_ratio(u128::from(config.jackpot_bps), u128::from(TOTAL_WEIGHT));
+    if !share.is_zero() {
+        let mut jackpot = JACKPOT.may_load(storage)?.unwrap_or_default();
+        add_coin(
+            &mut jackpot,
+            &Coin {
+                denom: fee.denom.clone(),
+                amount: share,
+            },
+        );
+        JACKPOT.save(storage, &jackpot)?;
+    }
+
+    Ok(Coin {
+        denom: fee.denom.clone(),
+        amount: fee.amount - share,
+    })
+}
+
+/// Whether winning `game` earns a draw for the jackpot.
+pub fn is_jackpot_eligible(config: &Config, game: &Game) -> bool {
+    // Games without a stake paid no fees into the jackpot, the house never plays for it
+    config.jackpot_odds > 0
+        && !game.bet.amount.is_zero()
+        && !(game.vs_house && game.winner == game.opponent)
+}
+
+/// Draw for the winner of `game`, decided `DRAW_DELAY` rounds after the `latest_round` of
+/// Terrand.
+pub fn new_draw(game: &Game, latest_round: u64) -> JackpotDraw {
+    JackpotDraw {
+        game_id: game.game_id,
+        winner: game.winner.clone(),
+        denom: game.bet.denom.clone(),
+        round: latest_round + DRAW_DELAY,
+    }
+}
+
+/// One in `odds` chance, drawn from the `randomness` of the round of the draw. The game id is
+/// mixed in so the games drawn in the same round do not all hit or miss together.
+pub fn lucky_draw(randomness: &Binary, game_id: u64, odds: u16) -> bool {
+    if odds == 0 {
+        return false;
+    }
+    let digest = Sha256::new()
+        .chain(randomness.as_slice())
+        .chain(game_id.to_be_bytes())
+        .finalize();
+    u16::from_be_bytes([digest[0], digest[1]]) % odds == 0
+}
+
+/// Takes everything collected in `denom` out of the jackpot.
+pub fn take_jackpot(storage: &mut dyn Storage, denom: &str) -> StdResult<Option<Coin>> {
+    let mut jackpot = JACKPOT.may_load(storage)?.unwrap_or_default();
+    match jackpot.iter().position(|coin| coin.denom == denom) {
+        None => Ok(None),
+        Some(pos) => {
+            let prize = jackpot.remove(pos);
+            JACKPOT.save(storage, &jackpot)?;
+            Ok(Some(prize))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::GameState;
+    use crate::testing;
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::{coin, Addr};
+
+    fn mock_config(jackpot_odds: u16) -> Config {
+        Config {
+            jackpot_bps: 2_500,
+            jackpot_odds,
+            ..testing::mock_config()
+        }
+    }
+
+    #[test]
+    fn fees_fund_the_jackpot() {
+        let mut storage = MockStorage::new();
+        let config = mock_config(0);
+
+        let rest = fund_jackpot(&mut storage, &config, &coin(1_000, "uust")).unwrap();
+        assert_eq!(coin(750, "uust"), rest);
+        let rest = fund_jackpot(&mut storage, &config, &coin(3, "uust")).unwrap();
+        assert_eq!(coin(3, "uust"), rest);
+        fund_jackpot(&mut storage, &config, &coin(400, "uluna")).unwrap();
+        assert_eq!(
+            vec![coin(100, "uluna"), coin(250, "uust")],
+            JACKPOT.load(&storage).unwrap()
+        );
+
+        assert_eq!(
+            Some(coin(250, "uust")),
+            take_jackpot(&mut storage, "uust").unwrap()
+        );
+        assert_eq!(None, take_jackpot(&mut storage, "uust").unwrap());
+        assert_eq!(vec![coin(100, "uluna")], JACKPOT.load(&storage).unwrap());
+    }
+
+    #[test]
+    fn only_staked_wins_over_players_are_drawn() {
+        let won = Game {
+            state: GameState::Completed,
+            winner: Addr::unchecked("host"),
+            ..testing::mock_game()
+        };
+        assert!(is_jackpot_eligible(&mock_config(10), &won));
+        assert!(!is_jackpot_eligible(&mock_config(0), &won));
+        let free = Game {
+            bet: coin(0, "uust"),
+            ..won.clone()
+        };
+        assert!(!is_jackpot_eligible(&mock_config(10), &free));
+        let house = Game {
+            winner: Addr::unchecked("opponent"),
+            vs_house: true,
+            ..won.clone()
+        };
+        assert!(!is_jackpot_eligible(&mock_config(10), &house));
+
+        let draw = new_draw(&won, 100);
+        assert_eq!(100 + DRAW_DELAY, draw.round);
+        assert_eq!(Addr::unchecked("host"), draw.winner);
+    }
+
+    #[test]
+    fn lucky_draw_depends_on_round_and_game() {
+        let randomness = Binary::from(&[7u8; 32][..]);
+        assert!(lucky_draw(&randomness, 1, 1));
+        assert!(!lucky_draw(&randomness, 1, 0));
+        let hits: Vec<bool> = (1..=16).map(|id| lucky_draw(&randomness, id, 2)).collect();
+        assert!(hits.contains(&true) && hits.contains(&false));
+        assert_eq!(hits[0], lucky_draw(&randomness, 1, 2));
+    }
+}
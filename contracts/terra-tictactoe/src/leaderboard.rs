@@ -0,0 +1,126 @@
+use cosmwasm_std::{Coin, Uint128};
+
+use crate::fees::TOTAL_WEIGHT;
+use crate::msg::LeaderBoardEntry;
+use crate::state::SeasonResult;
+use crate::utils::add_coin;
+
+/// Most places a leaderboard season can reward.
+pub const MAX_REWARDED_PLACES: usize = 10;
+
+/// Orders `entries` by wins, ties go to the lower address.
+pub fn rank_entries(entries: &mut [LeaderBoardEntry]) {
+    entries.sort_by(|a, b| {
+        b.win_count
+            .cmp(&a.win_count)
+            .then_with(|| a.player.cmp(&b.player))
+    });
+}
+
+/// Archives the top places of the `ranked` entries and pays them `reward_pool` by the basis
+/// points of `reward_distribution`. Returns the results of the top places and whatever is
+/// left of the pool when fewer players made it to the leaderboard or amounts were rounded
+/// down.
+pub fn season_rewards(
+    reward_pool: &[Coin],
+    reward_distribution: &[u16],
+    ranked: &[LeaderBoardEntry],
+) -> (Vec<SeasonResult>, Vec<Coin>) {
+    let mut results = vec![];
+    let mut leftover = reward_pool.to_vec();
+
+    for (place, entry) in ranked.iter().take(MAX_REWARDED_PLACES).enumerate() {
+        let bps = reward_distribution.get(place).copied().unwrap_or_default();
+        let mut reward = vec![];
+        for (coin, left) in reward_pool.iter().zip(leftover.iter_mut()) {
+            let amount = coin
+                .amount
+                .multiply_ratio(u128::from(bps), u128::from(TOTAL_WEIGHT));
+            if !amount.is_zero() {
+                left.amount -= amount;
+                add_coin(
+                    &mut reward,
+                    &Coin {
+                        denom: coin.denom.clone(),
+                        amount,
+                    },
+                );
+            }
+        }
+        results.push(SeasonResult {
+            player: entry.player.clone(),
+            win_count: entry.win_count,
+            reward,
+        });
+    }
+    leftover.retain(|coin| coin.amount > Uint128::zero());
+
+    (results, leftover)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, coins, Addr};
+
+    fn entry(player: &str, win_count: u64) -> LeaderBoardEntry {
+        LeaderBoardEntry {
+            player: Addr::unchecked(player),
+            win_count,
+        }
+    }
+
+    #[test]
+    fn entries_are_ranked_by_wins() {
+        let mut entries = vec![entry("carol", 2), entry("bob", 5), entry("alice", 2)];
+        rank_entries(&mut entries);
+        assert_eq!(
+            vec![entry("bob", 5), entry("alice", 2), entry("carol", 2)],
+            entries
+        );
+    }
+
+    #[test]
+    fn rewards_go_to_the_top_places() {
+        let pool = vec![coin(1_001, "uluna"), coin(3, "uust")];
+        let ranked = vec![entry("bob", 5), entry("alice", 2), entry("carol", 2)];
+
+        let (results, leftover) = season_rewards(&pool, &[5_000, 3_000, 2_000], &ranked);
+        assert_eq!(
+            vec![
+                SeasonResult {
+                    player: Addr::unchecked("bob"),
+                    win_count: 5,
+                    reward: vec![coin(500, "uluna"), coin(1, "uust")],
+                },
+                SeasonResult {
+                    player: Addr::unchecked("alice"),
+                    win_count: 2,
+                    reward: vec![coin(300, "uluna")],
+                },
+                SeasonResult {
+                    player: Addr::unchecked("carol"),
+                    win_count: 2,
+                    reward: vec![coin(200, "uluna")],
+                },
+            ],
+            results
+        );
+        assert_eq!(vec![coin(1, "uluna"), coin(2, "uust")], leftover);
+
+        // Places nobody reached leave their share in the pool
+        let (results, leftover) = season_rewards(&pool, &[5_000, 5_000], &ranked[..1]);
+        assert_eq!(1, results.len());
+        assert_eq!(vec![coin(501, "uluna"), coin(2, "uust")], leftover);
+
+        // Without rewards the top places are still archived
+        let (results, leftover) = season_rewards(&[], &[], &ranked);
+        assert_eq!(3, results.len());
+        assert!(results.iter().all(|result| result.reward.is_empty()));
+        assert!(leftover.is_empty());
+        assert_eq!(
+            coins(1, "uust"),
+            season_rewards(&coins(1, "uust"), &[], &ranked).1
+        );
+    }
+}
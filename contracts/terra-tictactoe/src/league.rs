@@ -0,0 +1,121 @@
+use cosmwasm_std::Addr;
+
+use crate::state::{LeaguePoints, Standing};
+
+/// Most players a season can take, every pair of them plays one game.
+pub const MAX_LEAGUE_PLAYERS: usize = 10;
+
+/// Outcome of a league game for one of its players.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// Schedules one game for every pair of `players` as `(host, opponent)`, alternating who
+/// gets to move first.
+pub fn round_robin(players: &[Addr]) -> Vec<(Addr, Addr)> {
+    let mut pairings = vec![];
+    for (i, first) in players.iter().enumerate() {
+        for (j, second) in players.iter().enumerate().skip(i + 1) {
+            if (i + j) % 2 == 0 {
+                pairings.push((first.clone(), second.clone()));
+            } else {
+                pairings.push((second.clone(), first.clone()));
+            }
+        }
+    }
+    pairings
+}
+
+/// Adds a game with `outcome` to `standing`.
+pub fn apply_outcome(standing: &mut Standing, outcome: Outcome, points: &LeaguePoints) {
+    standing.played += 1;
+    match outcome {
+        Outcome::Win => {
+            standing.wins += 1;
+            standing.points += points.win;
+        }
+        Outcome::Draw => {
+            standing.draws += 1;
+            standing.points += points.draw;
+        }
+        Outcome::Loss => {
+            standing.losses += 1;
+            standing.points += points.loss;
+        }
+    }
+}
+
+/// Orders `standings` by points, then wins, then fewest games played.
+pub fn sort_standings(standings: &mut [Standing]) {
+    standings.sort_by(|a, b| {
+        b.points
+            .cmp(&a.points)
+            .then(b.wins.cmp(&a.wins))
+            .then(a.played.cmp(&b.played))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standing(player: &str) -> Standing {
+        Standing {
+            player: Addr::unchecked(player),
+            played: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            points: 0,
+        }
+    }
+
+    #[test]
+    fn every_pair_plays_once() {
+        let players: Vec<Addr> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|p| Addr::unchecked(*p))
+            .collect();
+        let pairings = round_robin(&players);
+        assert_eq!(6, pairings.len());
+        for player in &players {
+            let games = pairings
+                .iter()
+                .filter(|(host, opponent)| host == player || opponent == player)
+                .count();
+            let hosted = pairings.iter().filter(|(host, _)| host == player).count();
+            assert_eq!(3, games);
+            assert!((1..=2).contains(&hosted));
+        }
+        assert!(round_robin(&players[..1]).is_empty());
+    }
+
+    #[test]
+    fn standings_follow_points() {
+        let points = LeaguePoints {
+            win: 3,
+            draw: 1,
+            loss: 0,
+        };
+        let mut a = standing("a");
+        let mut b = standing("b");
+        let mut c = standing("c");
+        apply_outcome(&mut a, Outcome::Win, &points);
+        apply_outcome(&mut b, Outcome::Loss, &points);
+        apply_outcome(&mut a, Outcome::Draw, &points);
+        apply_outcome(&mut c, Outcome::Draw, &points);
+        apply_outcome(&mut b, Outcome::Win, &points);
+        apply_outcome(&mut c, Outcome::Loss, &points);
+        assert_eq!(
+            (2, 1, 1, 0, 4),
+            (a.played, a.wins, a.draws, a.losses, a.points)
+        );
+
+        let mut standings = vec![c.clone(), b.clone(), a.clone()];
+        sort_standings(&mut standings);
+        assert_eq!(vec![a, b, c], standings);
+    }
+}
@@ -6,8 +6,24 @@ pub mod terrand;
 pub mod asserts;
 pub mod utils;
 pub mod game;
+pub mod events;
+pub mod settlement;
+pub mod referral;
+pub mod fees;
+pub mod jackpot;
+pub mod tournament;
+pub mod league;
+pub mod leaderboard;
+pub mod wagering;
+pub mod series;
+pub mod ultimate;
+pub mod house;
+pub mod session;
+pub mod signed;
 
 pub use crate::error::ContractError;
 
 #[cfg(test)]
-mod mock_querier;
\ No newline at end of file
+mod mock_querier;
+#[cfg(test)]
+mod testing;
@@ -6,7 +6,7 @@ use cosmwasm_std::{
 };
 use terra_cosmwasm::TerraQueryWrapper;
 
-use crate::terrand::{LatestRandomResponse, QueryMsg};
+use crate::terrand::{GetRandomResponse, LatestRandomResponse, QueryMsg};
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -28,12 +28,22 @@ pub struct WasmMockQuerier {
     terrand_querier: TerrandQuerier,
 }
 
-#[derive(Clone, Default)]
-pub struct TerrandQuerier {}
+#[derive(Clone)]
+pub struct TerrandQuerier {
+    latest_round: u64,
+}
+
+impl Default for TerrandQuerier {
+    fn default() -> Self {
+        TerrandQuerier {
+            latest_round: 1883848,
+        }
+    }
+}
 
 impl TerrandQuerier {
     pub fn new() -> Self {
-        TerrandQuerier {}
+        TerrandQuerier::default()
     }
 }
 
@@ -59,12 +69,24 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
                 Ok(QueryMsg::LatestDrand {}) => {
                     SystemResult::Ok(ContractResult::from(to_binary(&LatestRandomResponse {
-                        round: 1883848,
+                        round: self.terrand_querier.latest_round,
                         randomness: to_binary("yTBW2ubloeFa+ZRh08Jt+4jVQHHGMX4s3j8mTYKc3oQ=")
                             .unwrap(),
                         worker: "terra1qqhd8edcc590tdvy7zhhjt62e2gs49wcl22xaq".to_string(),
                     })))
                 }
+                Ok(QueryMsg::GetRandomness { round })
+                    if round <= self.terrand_querier.latest_round =>
+                {
+                    SystemResult::Ok(ContractResult::from(to_binary(&GetRandomResponse {
+                        randomness: to_binary("yTBW2ubloeFa+ZRh08Jt+4jVQHHGMX4s3j8mTYKc3oQ=")
+                            .unwrap(),
+                        worker: "terra1qqhd8edcc590tdvy7zhhjt62e2gs49wcl22xaq".to_string(),
+                    })))
+                }
+                Ok(QueryMsg::GetRandomness { .. }) => {
+                    SystemResult::Ok(ContractResult::Err("Randomness not found".to_string()))
+                }
                 _ => panic!("DO NOT ENTER HERE"),
             },
             _ => self.base.handle_query(request),
@@ -83,4 +105,8 @@ impl WasmMockQuerier {
     pub fn with_terrand(&mut self){
         self.terrand_querier = TerrandQuerier::new();
     }
+
+    pub fn with_latest_round(&mut self, round: u64) {
+        self.terrand_querier.latest_round = round;
+    }
 }
@@ -1,8 +1,12 @@
-use cosmwasm_std::{Addr, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::Game;
+use crate::state::{
+    BetMarket, BetOutcome, BetPosition, BoardRules, FeeTier, Game, House, JackpotDraw,
+    LeaderboardSeason, League, LeaguePoints, LeagueSeason, Move, RuleSet, Series, Session,
+    Standing, Tournament,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -20,26 +24,161 @@ pub enum ExecuteMsg {
     UpdateConfig {
         dimension: u8,
         threshold: u8,
-        fee_percentage: u8,
+        fee_bps: u16,
+        referral_percentage: u8,
+    },
+    UpdateFeeLimits {
+        max_fee_bps: u16,
+        fee_tiers: Vec<FeeTier>,
+    },
+    UpdateJackpot {
+        jackpot_bps: u16,
+        jackpot_odds: u16,
+    },
+    UpdateFeeCaps {
+        denom: String,
+        min_fee: Option<Uint128>,
+        max_fee: Option<Uint128>,
+    },
+    UpdateHouse {
+        exposure_bps: u16,
+        max_stake_bps: u16,
+        edge_bps: u16,
+    },
+    DepositHouse {},
+    WithdrawHouse {
+        denom: String,
+        shares: Uint128,
+    },
+    CreateGame {
+        #[serde(default)]
+        auto_payout: bool,
+        referrer: Option<String>,
+        #[serde(default)]
+        rule_set: RuleSet,
+        seats: Option<u8>,
+        #[serde(default)]
+        vs_house: bool,
     },
-    CreateGame {},
     CancelGame {
         game_id: u64,
     },
     JoinGame {
         game_id: u64,
+        referrer: Option<String>,
+    },
+    AddTeammate {
+        game_id: u64,
+        teammate: String,
     },
     MakeMove {
         game_id: u64,
         x: u8,
         y: u8,
     },
+    AuthorizeSessionKey {
+        key: String,
+        game_id: Option<u64>,
+        duration: Option<u64>,
+    },
+    RevokeSessionKey {
+        key: String,
+    },
+    RegisterMoveKey {
+        pubkey: Binary,
+    },
+    SubmitSignedMoves {
+        game_id: u64,
+        moves: Vec<SignedMove>,
+    },
+    ReportEquivocation {
+        game_id: u64,
+        player: String,
+        first: SignedMove,
+        second: SignedMove,
+    },
+    Resign {
+        game_id: u64,
+    },
+    OfferDraw {
+        game_id: u64,
+    },
+    AcceptDraw {
+        game_id: u64,
+    },
+    DeclineDraw {
+        game_id: u64,
+    },
+    RequestTakeback {
+        game_id: u64,
+    },
+    AcceptTakeback {
+        game_id: u64,
+    },
+    CreateSeries {
+        best_of: u8,
+    },
+    JoinSeries {
+        series_id: u64,
+    },
+    CancelSeries {
+        series_id: u64,
+    },
+    OfferRematch {
+        game_id: u64,
+    },
+    AcceptRematch {
+        game_id: u64,
+    },
+    DeclineRematch {
+        game_id: u64,
+    },
     WithdrawPrice {
         game_id: u64,
     },
-    WithdrawFunds {
-        funds_to_withdraw: Vec<Coin>,
+    ClaimAll {
+        limit: Option<u32>,
+    },
+    ClaimReferralRewards {},
+    UpdateFeeDistribution {
+        shares: Vec<FeeShareMsg>,
+    },
+    DistributeFees {},
+    DrawJackpot {
+        game_id: u64,
+    },
+    CreateTournament {
+        entry_fee: Coin,
+        bracket_size: u32,
+        rules: BoardRules,
+        prize_distribution: Vec<u16>,
+    },
+    RegisterForTournament {
+        tournament_id: u64,
+    },
+    CancelTournament {
+        tournament_id: u64,
+    },
+    UpdateBetting {
+        bets_close_after: u32,
+    },
+    PlaceBet {
+        game_id: u64,
+        outcome: BetOutcome,
+    },
+    ClaimBet {
+        game_id: u64,
+    },
+    StartSeason {
+        reward_distribution: Vec<u16>,
     },
+    ConfigureLeague {
+        season_duration: u64,
+        rules: BoardRules,
+        points: LeaguePoints,
+    },
+    JoinLeagueSeason {},
+    AdvanceLeague {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -47,10 +186,78 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     IsLocked {},
     GetAdmin {},
-    GetGames {},
-    GetGameById { id: u64 },
-    GetGamesByAddress { address: String },
+    GetGames {
+        rule_set: Option<RuleSet>,
+    },
+    GetGameById {
+        id: u64,
+    },
+    GetGamesByAddress {
+        address: String,
+        rule_set: Option<RuleSet>,
+    },
     GetLeaderboard {},
+    GetMoves {
+        game_id: u64,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    GetReferralStats {
+        address: String,
+    },
+    GetPendingDistribution {},
+    GetJackpot {},
+    GetJackpotDraw {
+        game_id: u64,
+    },
+    GetHouse {
+        denom: String,
+    },
+    GetSessionKey {
+        key: String,
+    },
+    GetMoveKey {
+        address: String,
+    },
+    GetHouseShares {
+        denom: String,
+        address: String,
+    },
+    GetSeries {
+        series_id: u64,
+    },
+    GetTournament {
+        tournament_id: u64,
+    },
+    GetTournaments {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetBetMarket {
+        game_id: u64,
+    },
+    GetBet {
+        game_id: u64,
+        address: String,
+    },
+    GetSeasonLeaderboard {
+        season_id: Option<u64>,
+    },
+    GetLeaderboardSeason {
+        season_id: u64,
+    },
+    GetLeague {},
+    GetStandings {
+        season_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FeeShareMsg {
+    pub name: String,
+    pub address: String,
+    pub weight: u16,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -71,6 +278,146 @@ pub struct GamesResponse {
     pub games: Vec<Game>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MovesResponse {
+    pub moves: Vec<Move>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ReferralStatsResponse {
+    pub referrer: Option<Addr>,
+    pub referred_players: u64,
+    pub pending_rewards: Vec<Coin>,
+    pub total_earned: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingDistributionResponse {
+    pub fee_pool: Vec<Coin>,
+    pub shares: Vec<PendingShare>,
+}
+
+/// A move made off-chain, signed by the players of the game over its `move_message`.
+/// `takebacks` is the count of takebacks accepted in the game when the move was made.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedMove {
+    pub takebacks: u32,
+    pub move_no: u32,
+    pub x: u8,
+    pub y: u8,
+    pub signatures: Vec<MoveSignature>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MoveSignature {
+    pub player: Addr,
+    pub signature: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MoveKeyResponse {
+    pub pubkey: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SessionKeyResponse {
+    pub session: Option<Session>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HouseResponse {
+    pub house: House,
+    pub max_exposure: Uint128,
+    pub exposure_bps: u16,
+    pub max_stake_bps: u16,
+    pub edge_bps: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HouseSharesResponse {
+    pub shares: Uint128,
+    pub value: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct JackpotResponse {
+    pub jackpot: Vec<Coin>,
+    pub jackpot_bps: u16,
+    pub jackpot_odds: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotDrawResponse {
+    pub draw: Option<JackpotDraw>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SeriesResponse {
+    pub series: Series,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TournamentResponse {
+    pub tournament: Tournament,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TournamentsResponse {
+    pub tournaments: Vec<Tournament>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BetMarketResponse {
+    pub market: BetMarket,
+    pub open: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BetResponse {
+    pub position: BetPosition,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LeaderboardSeasonResponse {
+    pub season: LeaderboardSeason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LeagueResponse {
+    pub league: League,
+    pub next_season: LeagueSeason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StandingsResponse {
+    pub season: LeagueSeason,
+    pub standings: Vec<Standing>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingShare {
+    pub name: String,
+    pub address: Addr,
+    pub weight: u16,
+    pub amount: Vec<Coin>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct LeaderboardResponse {
@@ -0,0 +1,46 @@
+use cosmwasm_std::{Addr, Api, Coin, StdResult, Storage};
+
+use crate::state::{ReferralStats, REFERRAL_STATS, REFERRERS};
+use crate::utils::add_coin;
+use crate::ContractError;
+
+/// Returns the referrer credited for the fees of `player`. The first referrer a player
+/// shows up with is kept for good, later ones are ignored.
+pub fn register_referral(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    player: &Addr,
+    referrer: Option<String>,
+) -> Result<Option<Addr>, ContractError> {
+    if let Some(existing) = REFERRERS.may_load(storage, player.clone())? {
+        return Ok(Some(existing));
+    }
+    let referrer = match referrer {
+        None => return Ok(None),
+        Some(referrer) => api.addr_validate(&referrer)?,
+    };
+    if referrer == *player {
+        return Err(ContractError::InvalidReferrer {});
+    }
+
+    REFERRERS.save(storage, player.clone(), &referrer)?;
+    REFERRAL_STATS.update(storage, referrer.clone(), |stats| -> StdResult<_> {
+        let mut stats = stats.unwrap_or_default();
+        stats.referred_players += 1;
+        Ok(stats)
+    })?;
+
+    Ok(Some(referrer))
+}
+
+/// Adds `reward` to the claimable balance of `referrer`.
+pub fn credit_referral(storage: &mut dyn Storage, referrer: &Addr, reward: &Coin) -> StdResult<()> {
+    REFERRAL_STATS.update(storage, referrer.clone(), |stats| -> StdResult<_> {
+        let mut stats: ReferralStats = stats.unwrap_or_default();
+        add_coin(&mut stats.pending_rewards, reward);
+        add_coin(&mut stats.total_earned, reward);
+        Ok(stats)
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use cosmwasm_std::Addr;
+
+use crate::state::{Game, GameState, Series, SeriesState};
+
+/// Games a player has to win to clinch a best-of series.
+pub fn wins_needed(best_of: u8) -> u32 {
+    u32::from(best_of / 2 + 1)
+}
+
+/// Adds the result of `game` to the score of `series`. Returns the player who clinched a
+/// best-of series with it, a drawn game is replayed without scoring.
+pub fn score_game(series: &mut Series, game: &Game) -> Option<Addr> {
+    if game.state == GameState::Drawn {
+        return None;
+    }
+    let pos = series.players.iter().position(|p| *p == game.winner)?;
+    series.wins[pos] += 1;
+
+    match series.best_of {
+        Some(best_of) if series.wins[pos] >= wins_needed(best_of) => {
+            series.state = SeriesState::Completed;
+            series.winner = Some(game.winner.clone());
+            series.winner.clone()
+        }
+        _ => None,
+    }
+}
+
+/// Pairs the players for the game after `game` as `(host, opponent)`, whoever moved second
+/// gets to move first.
+pub fn next_pairing(game: &Game) -> (Addr, Addr) {
+    (game.opponent.clone(), game.host.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::BoardRules;
+    use crate::testing;
+    use cosmwasm_std::coin;
+
+    fn mock_series(best_of: Option<u8>) -> Series {
+        Series {
+            series_id: 1,
+            players: vec![Addr::unchecked("host"), Addr::unchecked("opponent")],
+            best_of,
+            pot: coin(392, "uust"),
+            rules: BoardRules {
+                dimension: 6,
+                threshold: 4,
+            },
+            wins: vec![0, 0],
+            state: SeriesState::InProgress,
+            winner: None,
+            games: vec![1],
+        }
+    }
+
+    fn mock_game(state: GameState, host: &str, opponent: &str, winner: &str) -> Game {
+        Game {
+            host: Addr::unchecked(host),
+            bet: coin(0, "uust"),
+            state,
+            opponent: Addr::unchecked(opponent),
+            next_player: Addr::unchecked(host),
+            winner: Addr::unchecked(winner),
+            move_count: 7,
+            auto_payout: true,
+            series_id: Some(1),
+            players: vec![Addr::unchecked(host), Addr::unchecked(opponent)],
+            ..testing::mock_game()
+        }
+    }
+
+    #[test]
+    fn majority_of_games_clinches_the_series() {
+        assert_eq!(2, wins_needed(3));
+        assert_eq!(4, wins_needed(7));
+
+        let mut series = mock_series(Some(3));
+        let won = |winner| mock_game(GameState::PriceWithdrawn, "host", "opponent", winner);
+        assert_eq!(None, score_game(&mut series, &won("host")));
+        let drawn = mock_game(GameState::Drawn, "opponent", "host", "");
+        assert_eq!(None, score_game(&mut series, &drawn));
+        assert_eq!(None, score_game(&mut series, &won("opponent")));
+        assert_eq!(vec![1, 1], series.wins);
+        assert_eq!(
+            Some(Addr::unchecked("opponent")),
+            score_game(&mut series, &won("opponent"))
+        );
+        assert_eq!(SeriesState::Completed, series.state);
+
+        // A series of rematches only keeps the score
+        let mut series = mock_series(None);
+        for _ in 0..5 {
+            assert_eq!(None, score_game(&mut series, &won("host")));
+        }
+        assert_eq!(vec![5, 0], series.wins);
+    }
+
+    #[test]
+    fn first_move_alternates() {
+        let game = mock_game(GameState::PriceWithdrawn, "host", "opponent", "host");
+        assert_eq!(
+            (Addr::unchecked("opponent"), Addr::unchecked("host")),
+            next_pairing(&game)
+        );
+    }
+}
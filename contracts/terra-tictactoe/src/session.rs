@@ -0,0 +1,58 @@
+use cosmwasm_std::{Addr, Timestamp};
+
+use crate::state::Session;
+use crate::ContractError;
+
+/// Sets up a session key for `player`, bound to a single game, to `duration` seconds from
+/// `now` or to both. A key bound to neither would act for the player forever.
+pub fn new_session(
+    player: Addr,
+    game_id: Option<u64>,
+    duration: Option<u64>,
+    now: Timestamp,
+) -> Result<Session, ContractError> {
+    if game_id.is_none() && duration.is_none() {
+        return Err(ContractError::InvalidSessionKey {});
+    }
+    Ok(Session {
+        player,
+        game_id,
+        expires_at: duration.map(|seconds| now.plus_seconds(seconds)),
+    })
+}
+
+/// Whether the key of `session` may act for its player in game `game_id` at `now`.
+pub fn is_session_valid(session: &Session, game_id: u64, now: Timestamp) -> bool {
+    session.game_id.map_or(true, |id| id == game_id)
+        && session
+            .expires_at
+            .map_or(true, |expires_at| now < expires_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_are_bound_to_a_game_or_a_duration() {
+        let now = Timestamp::from_seconds(1_000);
+        let player = Addr::unchecked("player");
+        match new_session(player.clone(), None, None, now) {
+            Err(ContractError::InvalidSessionKey {}) => {}
+            _ => panic!("Must return InvalidSessionKey error"),
+        }
+
+        let session = new_session(player.clone(), Some(2), None, now).unwrap();
+        assert!(is_session_valid(&session, 2, now.plus_seconds(100_000)));
+        assert!(!is_session_valid(&session, 3, now));
+
+        let session = new_session(player.clone(), None, Some(600), now).unwrap();
+        assert!(is_session_valid(&session, 3, now.plus_seconds(599)));
+        assert!(!is_session_valid(&session, 3, now.plus_seconds(600)));
+
+        let session = new_session(player, Some(2), Some(600), now).unwrap();
+        assert!(is_session_valid(&session, 2, now));
+        assert!(!is_session_valid(&session, 2, now.plus_seconds(600)));
+        assert!(!is_session_valid(&session, 3, now));
+    }
+}
@@ -0,0 +1,351 @@
+use cosmwasm_std::{Addr, Coin, Timestamp, Uint128};
+
+use crate::game::{active_players, get_opponent_of};
+use crate::state::{Game, GameState};
+use crate::ContractError;
+
+/// Seconds a player may take for a move before the other player can claim the pot.
+pub const MOVE_TIMEOUT: u64 = 3600;
+
+/// Transitions between the states of a game:
+///
+/// - `New` -> `InProgress`: the last free seat is taken with a matching stake.
+/// - `New` -> `Cancelled`: the host cancels, the stakes of the seated players are refunded.
+/// - `InProgress` -> `Completed`: a winning move or a resignation, the winner can claim the pot.
+///   With more than two seats a resigning player leaves the game and the others play on, the
+///   game is won by the last player left.
+/// - `InProgress` -> `Drawn`: a draw offer is accepted, the stakes are refunded.
+/// - `InProgress` -> `PriceWithdrawn`: the player waiting on a stalled opponent claims the pot
+///   after `MOVE_TIMEOUT`, or a game created with `auto_payout` is won. With more than two
+///   seats the pot is split between every player but the stalled one.
+/// - `Completed` -> `PriceWithdrawn`: the winner claims the pot.
+///
+/// `PriceWithdrawn`, `Cancelled` and `Drawn` are final, nothing is ever paid out of them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settlement {
+    pub state: GameState,
+    pub winner: Option<Addr>,
+    pub payouts: Vec<(Addr, Coin)>,
+}
+
+/// Settles whatever `caller` is owed by `game` at `now`. Only the rightful recipient of a
+/// payout can trigger it and every payout moves the game into a final state.
+pub fn settle(game: &Game, caller: &Addr, now: Timestamp) -> Result<Settlement, ContractError> {
+    match game.state {
+        GameState::New if *caller == game.host => Ok(Settlement {
+            state: GameState::Cancelled,
+            winner: None,
+            payouts: split_pot(&game.bet, &game.players),
+        }),
+        GameState::Completed if *caller == game.winner => Ok(Settlement {
+            state: GameState::PriceWithdrawn,
+            winner: None,
+            payouts: vec![(game.winner.clone(), game.bet.clone())],
+        }),
+        GameState::InProgress if game.players.len() > 2 => {
+            if *caller == game.next_player || !active_players(game).contains(caller) {
+                Err(ContractError::Unauthorized {})
+            } else if game.updated_at.seconds() + MOVE_TIMEOUT >= now.seconds() {
+                Err(ContractError::PriceCannotBeWithdrawn {})
+            } else {
+                Ok(Settlement {
+                    state: GameState::PriceWithdrawn,
+                    winner: None,
+                    payouts: forfeit(game, &game.next_player),
+                })
+            }
+        }
+        GameState::InProgress => {
+            // The stalled player is the one whose turn it is, the pot goes to the other one
+            let waiting_player = get_opponent_of(game, &game.next_player)?;
+            if *caller != waiting_player {
+                Err(ContractError::Unauthorized {})
+            } else if game.updated_at.seconds() + MOVE_TIMEOUT >= now.seconds() {
+                Err(ContractError::PriceCannotBeWithdrawn {})
+            } else {
+                Ok(Settlement {
+                    state: GameState::PriceWithdrawn,
+                    winner: Some(waiting_player.clone()),
+                    payouts: vec![(waiting_player, game.bet.clone())],
+                })
+            }
+        }
+        GameState::New | GameState::Completed => Err(ContractError::Unauthorized {}),
+        GameState::PriceWithdrawn | GameState::Cancelled | GameState::Drawn => {
+            Err(ContractError::PriceCannotBeWithdrawn {})
+        }
+    }
+}
+
+/// Splits `pot` evenly between `players`, the first of them receives any remainder.
+pub fn split_pot(pot: &Coin, players: &[Addr]) -> Vec<(Addr, Coin)> {
+    if players.is_empty() {
+        return vec![];
+    }
+    let share = pot.amount.multiply_ratio(1u128, players.len() as u128);
+    let remainder = pot.amount - share * Uint128::from(players.len() as u128);
+    players
+        .iter()
+        .enumerate()
+        .map(|(seat, player)| {
+            let amount = if seat == 0 { share + remainder } else { share };
+            (
+                player.clone(),
+                Coin {
+                    denom: pot.denom.clone(),
+                    amount,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Splits the pot of `game` between the players still in it but `player`, who forfeits their
+/// seat.
+pub fn forfeit(game: &Game, player: &Addr) -> Vec<(Addr, Coin)> {
+    let others: Vec<Addr> = active_players(game)
+        .into_iter()
+        .filter(|p| p != player)
+        .collect();
+    split_pot(&game.bet, &others)
+}
+
+/// Splits the share of every seat in `payouts` between the player holding it and their
+/// teammates.
+pub fn share_with_teams(game: &Game, payouts: Vec<(Addr, Coin)>) -> Vec<(Addr, Coin)> {
+    payouts
+        .into_iter()
+        .flat_map(|(recipient, amount)| {
+            let team = game
+                .players
+                .iter()
+                .position(|p| *p == recipient)
+                .and_then(|seat| game.teams.get(seat))
+                .filter(|team| !team.is_empty());
+            match team {
+                Some(team) => {
+                    let mut members = vec![recipient];
+                    members.extend(team.iter().cloned());
+                    split_pot(&amount, &members)
+                }
+                None => vec![(recipient, amount)],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::BoardRules;
+    use crate::testing;
+    use cosmwasm_std::coin;
+
+    const HOST: &str = "host";
+    const OPPONENT: &str = "opponent";
+    const STRANGER: &str = "stranger";
+
+    fn mock_game(state: GameState, next_player: &str, winner: &str) -> Game {
+        Game {
+            grid: vec![vec![0; 3]; 3],
+            next_player: Addr::unchecked(next_player),
+            winner: Addr::unchecked(winner),
+            rules: BoardRules {
+                dimension: 3,
+                threshold: 3,
+            },
+            players: match state {
+                GameState::New => vec![Addr::unchecked(HOST)],
+                _ => vec![Addr::unchecked(HOST), Addr::unchecked(OPPONENT)],
+            },
+            state,
+            ..testing::mock_game()
+        }
+    }
+
+    fn before_timeout() -> Timestamp {
+        Timestamp::from_seconds(1_000 + MOVE_TIMEOUT)
+    }
+
+    fn after_timeout() -> Timestamp {
+        Timestamp::from_seconds(1_000 + MOVE_TIMEOUT + 1)
+    }
+
+    fn payout_to(state: GameState, recipient: &str, winner: Option<&str>) -> Settlement {
+        Settlement {
+            state,
+            winner: winner.map(Addr::unchecked),
+            payouts: vec![(Addr::unchecked(recipient), coin(196, "uust"))],
+        }
+    }
+
+    fn assert_settles(game: &Game, caller: &str, now: Timestamp, expected: Settlement) {
+        assert_eq!(
+            expected,
+            settle(game, &Addr::unchecked(caller), now).unwrap(),
+            "state {:?}, caller {}",
+            game.state,
+            caller
+        );
+    }
+
+    fn assert_unauthorized(game: &Game, caller: &str, now: Timestamp) {
+        match settle(game, &Addr::unchecked(caller), now) {
+            Err(ContractError::Unauthorized {}) => {}
+            res => panic!(
+                "state {:?}, caller {}: expected Unauthorized, got {:?}",
+                game.state, caller, res
+            ),
+        }
+    }
+
+    fn assert_nothing_to_withdraw(game: &Game, caller: &str, now: Timestamp) {
+        match settle(game, &Addr::unchecked(caller), now) {
+            Err(ContractError::PriceCannotBeWithdrawn {}) => {}
+            res => panic!(
+                "state {:?}, caller {}: expected PriceCannotBeWithdrawn, got {:?}",
+                game.state, caller, res
+            ),
+        }
+    }
+
+    #[test]
+    fn new_game_refunds_only_the_host() {
+        let game = mock_game(GameState::New, HOST, "");
+        for &now in [before_timeout(), after_timeout()].iter() {
+            assert_settles(
+                &game,
+                HOST,
+                now,
+                payout_to(GameState::Cancelled, HOST, None),
+            );
+            assert_unauthorized(&game, OPPONENT, now);
+            assert_unauthorized(&game, STRANGER, now);
+        }
+    }
+
+    #[test]
+    fn in_progress_game_pays_the_waiting_player_after_timeout() {
+        // Host stalled on the very first move, the opponent never moved
+        let game = mock_game(GameState::InProgress, HOST, "");
+        assert_nothing_to_withdraw(&game, OPPONENT, before_timeout());
+        assert_settles(
+            &game,
+            OPPONENT,
+            after_timeout(),
+            payout_to(GameState::PriceWithdrawn, OPPONENT, Some(OPPONENT)),
+        );
+        for &now in [before_timeout(), after_timeout()].iter() {
+            assert_unauthorized(&game, HOST, now);
+            assert_unauthorized(&game, STRANGER, now);
+        }
+
+        // Opponent stalled
+        let game = mock_game(GameState::InProgress, OPPONENT, "");
+        assert_nothing_to_withdraw(&game, HOST, before_timeout());
+        assert_settles(
+            &game,
+            HOST,
+            after_timeout(),
+            payout_to(GameState::PriceWithdrawn, HOST, Some(HOST)),
+        );
+        for &now in [before_timeout(), after_timeout()].iter() {
+            assert_unauthorized(&game, OPPONENT, now);
+            assert_unauthorized(&game, STRANGER, now);
+        }
+    }
+
+    #[test]
+    fn completed_game_pays_only_the_winner() {
+        for &(winner, loser) in [(HOST, OPPONENT), (OPPONENT, HOST)].iter() {
+            let game = mock_game(GameState::Completed, loser, winner);
+            for &now in [before_timeout(), after_timeout()].iter() {
+                assert_settles(
+                    &game,
+                    winner,
+                    now,
+                    payout_to(GameState::PriceWithdrawn, winner, None),
+                );
+                assert_unauthorized(&game, loser, now);
+                assert_unauthorized(&game, STRANGER, now);
+            }
+        }
+    }
+
+    #[test]
+    fn stalled_player_forfeits_to_the_other_seats() {
+        let mut game = mock_game(GameState::InProgress, OPPONENT, "");
+        game.players.push(Addr::unchecked(STRANGER));
+        game.seats = 3;
+        assert_nothing_to_withdraw(&game, HOST, before_timeout());
+        assert_settles(
+            &game,
+            STRANGER,
+            after_timeout(),
+            Settlement {
+                state: GameState::PriceWithdrawn,
+                winner: None,
+                payouts: vec![
+                    (Addr::unchecked(HOST), coin(98, "uust")),
+                    (Addr::unchecked(STRANGER), coin(98, "uust")),
+                ],
+            },
+        );
+        assert_unauthorized(&game, OPPONENT, after_timeout());
+        assert_unauthorized(&game, "outsider", after_timeout());
+    }
+
+    #[test]
+    fn pot_splits_evenly() {
+        let players = vec![HOST, OPPONENT, STRANGER]
+            .into_iter()
+            .map(Addr::unchecked)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (players[0].clone(), coin(66, "uust")),
+                (players[1].clone(), coin(65, "uust")),
+                (players[2].clone(), coin(65, "uust")),
+            ],
+            split_pot(&coin(196, "uust"), &players)
+        );
+        assert!(split_pot(&coin(196, "uust"), &[]).is_empty());
+    }
+
+    #[test]
+    fn team_shares_are_split_among_members() {
+        let mut game = mock_game(GameState::Completed, HOST, HOST);
+        game.teams = vec![vec![Addr::unchecked(STRANGER)], vec![]];
+        let payouts = vec![
+            (Addr::unchecked(HOST), coin(197, "uust")),
+            (Addr::unchecked(OPPONENT), coin(50, "uust")),
+        ];
+        assert_eq!(
+            vec![
+                (Addr::unchecked(HOST), coin(99, "uust")),
+                (Addr::unchecked(STRANGER), coin(98, "uust")),
+                (Addr::unchecked(OPPONENT), coin(50, "uust")),
+            ],
+            share_with_teams(&game, payouts)
+        );
+    }
+
+    #[test]
+    fn final_states_never_pay_out() {
+        for state in [
+            GameState::PriceWithdrawn,
+            GameState::Cancelled,
+            GameState::Drawn,
+        ]
+        .iter()
+        .cloned()
+        {
+            let game = mock_game(state, HOST, HOST);
+            for &now in [before_timeout(), after_timeout()].iter() {
+                for &caller in [HOST, OPPONENT, STRANGER].iter() {
+                    assert_nothing_to_withdraw(&game, caller, now);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use cosmwasm_std::{Addr, Api, Binary, Env};
+use sha2::{Digest, Sha256};
+
+use crate::msg::SignedMove;
+use crate::ContractError;
+
+/// Bytes a player signs for a move made off-chain: the chain id and the address of this
+/// contract, each prefixed with its length, so that a signature is good for this deployment
+/// only, then the game id, the count of takebacks and the move number as big endian integers
+/// followed by the coordinates of the move.
+pub fn move_message(
+    env: &Env,
+    game_id: u64,
+    takebacks: u32,
+    move_no: u32,
+    x: u8,
+    y: u8,
+) -> Vec<u8> {
+    let chain_id = env.block.chain_id.as_bytes();
+    let contract = env.contract.address.as_bytes();
+    let mut message = Vec::with_capacity(26 + chain_id.len() + contract.len());
+    message.extend_from_slice(&(chain_id.len() as u32).to_be_bytes());
+    message.extend_from_slice(chain_id);
+    message.extend_from_slice(&(contract.len() as u32).to_be_bytes());
+    message.extend_from_slice(contract);
+    message.extend_from_slice(&game_id.to_be_bytes());
+    message.extend_from_slice(&takebacks.to_be_bytes());
+    message.extend_from_slice(&move_no.to_be_bytes());
+    message.extend_from_slice(&[x, y]);
+    message
+}
+
+/// SHA-256 hash of the `move_message` of `signed`, the signatures are made over it.
+pub fn move_digest(env: &Env, game_id: u64, signed: &SignedMove) -> Vec<u8> {
+    Sha256::digest(&move_message(
+        env,
+        game_id,
+        signed.takebacks,
+        signed.move_no,
+        signed.x,
+        signed.y,
+    ))
+    .to_vec()
+}
+
+/// Whether `player` signed `signed` with the key `pubkey`. A signature that does not verify
+/// is an error, a missing one is not.
+pub fn is_signed_by(
+    api: &dyn Api,
+    env: &Env,
+    game_id: u64,
+    signed: &SignedMove,
+    player: &Addr,
+    pubkey: &Binary,
+) -> Result<bool, ContractError> {
+    let signature = match signed.signatures.iter().find(|s| s.player == *player) {
+        None => return Ok(false),
+        Some(signature) => signature,
+    };
+    let digest = move_digest(env, game_id, signed);
+    match api.secp256k1_verify(&digest, &signature.signature, pubkey) {
+        Ok(true) => Ok(true),
+        _ => Err(ContractError::InvalidSignature {}),
+    }
+}
+
+/// Whether `first` and `second` are two different moves for the same move number. Moves made
+/// before and after a takeback are not, the move taken back is made again.
+pub fn is_equivocation(first: &SignedMove, second: &SignedMove) -> bool {
+    (first.takebacks, first.move_no) == (second.takebacks, second.move_no)
+        && (first.x, first.y) != (second.x, second.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::MoveSignature;
+    use cosmwasm_std::testing::mock_env;
+
+    fn mock_move(move_no: u32, x: u8, y: u8) -> SignedMove {
+        SignedMove {
+            takebacks: 0,
+            move_no,
+            x,
+            y,
+            signatures: vec![MoveSignature {
+                player: Addr::unchecked("player"),
+                signature: Binary::default(),
+            }],
+        }
+    }
+
+    #[test]
+    fn message_layout() {
+        let mut env = mock_env();
+        env.block.chain_id = "test-1".to_string();
+        env.contract.address = Addr::unchecked("ttt");
+        assert_eq!(
+            vec![
+                0, 0, 0, 6, b't', b'e', b's', b't', b'-', b'1', 0, 0, 0, 3, b't', b't', b't', 0, 0,
+                0, 0, 0, 0, 1, 2, 0, 0, 0, 1, 0, 0, 0, 3, 4, 5
+            ],
+            move_message(&env, 258, 1, 3, 4, 5)
+        );
+        assert_eq!(32, move_digest(&env, 258, &mock_move(3, 4, 5)).len());
+        assert_ne!(
+            move_digest(&env, 258, &mock_move(3, 4, 5)),
+            move_digest(&env, 259, &mock_move(3, 4, 5))
+        );
+    }
+
+    #[test]
+    fn signatures_are_bound_to_the_deployment() {
+        let env = mock_env();
+        let mut other_contract = mock_env();
+        other_contract.contract.address = Addr::unchecked("other_contract");
+        let mut other_chain = mock_env();
+        other_chain.block.chain_id = "other-chain".to_string();
+        let digest = move_digest(&env, 1, &mock_move(3, 4, 5));
+        assert_ne!(digest, move_digest(&other_contract, 1, &mock_move(3, 4, 5)));
+        assert_ne!(digest, move_digest(&other_chain, 1, &mock_move(3, 4, 5)));
+    }
+
+    #[test]
+    fn equivocation_needs_two_moves_for_the_same_number() {
+        assert!(is_equivocation(&mock_move(3, 4, 5), &mock_move(3, 5, 4)));
+        assert!(!is_equivocation(&mock_move(3, 4, 5), &mock_move(3, 4, 5)));
+        assert!(!is_equivocation(&mock_move(3, 4, 5), &mock_move(4, 5, 4)));
+        let retaken = SignedMove {
+            takebacks: 1,
+            ..mock_move(3, 5, 4)
+        };
+        assert!(!is_equivocation(&mock_move(3, 4, 5), &retaken));
+    }
+}
@@ -1,18 +1,71 @@
-use cosmwasm_std::{Addr, Coin, StdResult, Storage, Timestamp};
+use cosmwasm_std::{Addr, Binary, Coin, StdResult, Storage, Timestamp, Uint128};
 
 use cw_controllers::Admin;
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, U64Key};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, U32Key, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub locked: bool,
-    pub fee_percentage: u8,
+    pub fee_bps: u16,
+    pub max_fee_bps: u16,
+    pub fee_tiers: Vec<FeeTier>,
     pub threshold: u8,
     pub dimension: u8,
     pub min_bet: Coin,
     pub terrand_address: Addr,
+    pub referral_percentage: u8,
+    pub jackpot_bps: u16,
+    pub jackpot_odds: u16,
+    pub bets_close_after: u32,
+    pub house_exposure_bps: u16,
+    pub house_max_stake_bps: u16,
+    pub house_edge_bps: u16,
+}
+
+/// Fee charged on stakes of at least `min_stake`, the tier with the highest matching
+/// `min_stake` wins over the base fee.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeTier {
+    pub min_stake: Uint128,
+    pub fee_bps: u16,
+}
+
+/// Absolute bounds of the fee charged in a single denom.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct FeeCaps {
+    pub min_fee: Option<Uint128>,
+    pub max_fee: Option<Uint128>,
+}
+
+/// Funds of the house in a single denom, `exposure` is its stake in running games and
+/// `bankroll` whatever is left to match new ones with. Liquidity providers own the pool
+/// through `shares`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct House {
+    pub bankroll: Uint128,
+    pub exposure: Uint128,
+    pub shares: Uint128,
+}
+
+/// Jackpot draw owed to the winner of game `game_id`, decided by the Terrand `round` which was
+/// not out yet when the game was won.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotDraw {
+    pub game_id: u64,
+    pub winner: Addr,
+    pub denom: String,
+    pub round: u64,
+}
+
+/// Authorization of a session key to move and resign for `player`, in game `game_id` only
+/// and until `expires_at` when set. It never moves funds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Session {
+    pub player: Addr,
+    pub game_id: Option<u64>,
+    pub expires_at: Option<Timestamp>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -26,6 +79,253 @@ pub struct Game {
     pub next_player: Addr,
     pub updated_at: Timestamp,
     pub winner: Addr,
+    pub move_count: u32,
+    pub pending_offer: Option<Offer>,
+    pub auto_payout: bool,
+    pub seed: Binary,
+    pub rules: BoardRules,
+    pub tournament_id: Option<u64>,
+    pub season_id: Option<u64>,
+    pub series_id: Option<u64>,
+    pub rule_set: RuleSet,
+    pub ultimate: Option<UltimateBoard>,
+    /// Seats taken so far in turn order, `host` and `opponent` hold the first two of them.
+    pub players: Vec<Addr>,
+    pub seats: u8,
+    /// Teammates sharing each seat with the player holding it, in the order of `players`.
+    pub teams: Vec<Vec<Addr>>,
+    /// Played against the house, which holds the `opponent` seat and replies to every move.
+    pub vs_house: bool,
+    /// Players who resigned from a game of more than two seats, their turns are skipped.
+    pub resigned: Vec<Addr>,
+    /// Takebacks accepted so far, moves signed off-chain are only good for the current count.
+    pub takebacks: u32,
+}
+
+/// Variant of the game played on the grid. `Gomoku` is won by exactly five in a line,
+/// longer lines do not count, in `ConnectFour` marks drop to the lowest empty cell of their
+/// column and in `Misere` the player completing a line loses. `Ultimate` is not played on
+/// the grid but on an `UltimateBoard`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleSet {
+    Classic,
+    Gomoku,
+    ConnectFour,
+    Misere,
+    Ultimate,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::Classic
+    }
+}
+
+/// Board of ultimate tic-tac-toe, a 3x3 grid of 3x3 sub-boards. `cells` holds the marks of
+/// each sub-board in turn, `boards` the mark of the player who won each sub-board or -1 once
+/// it is full. `forced` is the sub-board the next move has to be played in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UltimateBoard {
+    pub cells: Vec<i8>,
+    pub boards: Vec<i8>,
+    pub forced: Option<u8>,
+}
+
+/// Size of the board and number of marks in a line needed to win, also the number of
+/// cells disabled at random when the board is set up.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct BoardRules {
+    pub dimension: u8,
+    pub threshold: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Offer {
+    pub kind: OfferKind,
+    pub from: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum OfferKind {
+    Draw,
+    Takeback,
+    Rematch,
+}
+
+/// Games played back to back between the same two players, in order. A best-of series is
+/// played for a single `pot` paid out once a player clinches it, its games have no stake of
+/// their own. A series of rematches only keeps the score, its `pot` stays empty.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Series {
+    pub series_id: u64,
+    pub players: Vec<Addr>,
+    pub best_of: Option<u8>,
+    pub pot: Coin,
+    pub rules: BoardRules,
+    pub wins: Vec<u32>,
+    pub state: SeriesState,
+    pub winner: Option<Addr>,
+    pub games: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum SeriesState {
+    Open,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Move {
+    pub move_no: u32,
+    pub player: Addr,
+    pub x: u8,
+    pub y: u8,
+    pub time: Timestamp,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeShare {
+    pub name: String,
+    pub address: Addr,
+    pub weight: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ReferralStats {
+    pub referred_players: u64,
+    pub pending_rewards: Vec<Coin>,
+    pub total_earned: Vec<Coin>,
+}
+
+/// A single-elimination tournament. Once `bracket_size` players registered they are seeded
+/// into `bracket` and play the games of the current round in pairs: `games[i]` is played
+/// between `bracket[2 * i]` and `bracket[2 * i + 1]`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Tournament {
+    pub tournament_id: u64,
+    pub creator: Addr,
+    pub entry_fee: Coin,
+    pub bracket_size: u32,
+    pub rules: BoardRules,
+    /// Basis points of the prize pool for the champion, the runner-up, the semi-finalists
+    /// and so on, shared equally by the players knocked out in the same round.
+    pub prize_distribution: Vec<u16>,
+    pub state: TournamentState,
+    pub players: Vec<Addr>,
+    pub round: u32,
+    pub bracket: Vec<Addr>,
+    pub games: Vec<u64>,
+    pub winners: Vec<Option<Addr>>,
+    /// Players knocked out in each round so far.
+    pub eliminated: Vec<Vec<Addr>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum TournamentState {
+    Registration,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+/// Settings of the round-robin league, every season is started with a copy of them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct League {
+    pub season_duration: u64,
+    pub rules: BoardRules,
+    pub points: LeaguePoints,
+    /// The season currently open for registration.
+    pub next_season: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct LeaguePoints {
+    pub win: u32,
+    pub draw: u32,
+    pub loss: u32,
+}
+
+/// A league season. Players register until `start`, then every pair of them gets a game
+/// scheduled and results count towards the standings until `end`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeagueSeason {
+    pub season_id: u64,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub rules: BoardRules,
+    pub points: LeaguePoints,
+    pub state: SeasonState,
+    pub players: Vec<Addr>,
+    pub games: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum SeasonState {
+    Registration,
+    InProgress,
+    Finished,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Standing {
+    pub player: Addr,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points: u32,
+}
+
+/// A season of the leaderboard. Wins count towards the season running at the time, when
+/// the next season starts the top of the season is archived in `results` and paid out of
+/// `reward_pool` by the basis points of `reward_distribution`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardSeason {
+    pub season_id: u64,
+    pub started_at: Timestamp,
+    pub ended_at: Option<Timestamp>,
+    pub reward_pool: Vec<Coin>,
+    pub reward_distribution: Vec<u16>,
+    pub results: Vec<SeasonResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeasonResult {
+    pub player: Addr,
+    pub win_count: u64,
+    pub reward: Vec<Coin>,
+}
+
+/// Side bets of spectators on the outcome of a game.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BetMarket {
+    pub game_id: u64,
+    pub denom: String,
+    pub pools: BetPosition,
+    /// House fee taken out of the pool, fixed by the first claim.
+    pub fee: Option<Uint128>,
+    /// Winning stake claimed so far and what it was paid.
+    pub claimed: Uint128,
+    pub paid: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+pub struct BetPosition {
+    pub host: Uint128,
+    pub opponent: Uint128,
+    pub draw: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BetOutcome {
+    Host,
+    Opponent,
+    Draw,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -35,16 +335,18 @@ pub enum GameState {
     Completed,
     PriceWithdrawn,
     Cancelled,
+    Drawn,
 }
 
 pub struct GamesIndexes<'a> {
     pub host: MultiIndex<'a, (Addr, U64Key), Game>,
     pub opponent: MultiIndex<'a, (Addr, U64Key), Game>,
+    pub winner: MultiIndex<'a, (Addr, U64Key), Game>,
 }
 
 impl IndexList<Game> for GamesIndexes<'_> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Game>> + '_> {
-        let v: Vec<&dyn Index<Game>> = vec![&self.host, &self.opponent];
+        let v: Vec<&dyn Index<Game>> = vec![&self.host, &self.opponent, &self.winner];
         Box::new(v.into_iter())
     }
 }
@@ -61,6 +363,11 @@ pub fn games<'a>() -> IndexedMap<'a, U64Key, Game, GamesIndexes<'a>> {
             "games",
             "game__opponent",
         ),
+        winner: MultiIndex::new(
+            |game: &Game, _key| (game.winner.clone(), U64Key::new(game.game_id)),
+            "games",
+            "games__winner",
+        ),
     };
     IndexedMap::new("games", indexes)
 }
@@ -70,9 +377,48 @@ pub const ADMIN: Admin = Admin::new("admin");
 
 pub const GAMES_COUNT: Item<u64> = Item::new("game_count");
 pub const LEADERBOARD: Map<Addr, u64> = Map::new("leaderboard");
+pub const MOVES: Map<(U64Key, U32Key), Move> = Map::new("moves");
+/// Id of every game an address holds a seat after the second one in or is a teammate in,
+/// the `host` and `opponent` indexes of `games` cover the first two seats.
+pub const GAME_MEMBERS: Map<(Addr, U64Key), u64> = Map::new("game_members");
+pub const FEE_POOL: Item<Vec<Coin>> = Item::new("fee_pool");
+pub const FEE_DISTRIBUTION: Item<Vec<FeeShare>> = Item::new("fee_distribution");
+pub const JACKPOT: Item<Vec<Coin>> = Item::new("jackpot");
+pub const JACKPOT_DRAWS: Map<U64Key, JackpotDraw> = Map::new("jackpot_draws");
+pub const SERIES_COUNT: Item<u64> = Item::new("series_count");
+pub const SERIES: Map<U64Key, Series> = Map::new("series");
+pub const TOURNAMENTS_COUNT: Item<u64> = Item::new("tournaments_count");
+pub const TOURNAMENTS: Map<U64Key, Tournament> = Map::new("tournaments");
+pub const CURRENT_SEASON: Item<u64> = Item::new("current_season");
+pub const LEADERBOARD_SEASONS: Map<U64Key, LeaderboardSeason> = Map::new("leaderboard_seasons");
+pub const SEASON_LEADERBOARD: Map<(U64Key, Addr), u64> = Map::new("season_leaderboard");
+pub const BET_MARKETS: Map<U64Key, BetMarket> = Map::new("bet_markets");
+pub const BETS: Map<(U64Key, Addr), BetPosition> = Map::new("bets");
+pub const LEAGUE: Item<League> = Item::new("league");
+pub const LEAGUE_SEASONS: Map<U64Key, LeagueSeason> = Map::new("league_seasons");
+pub const STANDINGS: Map<(U64Key, Addr), Standing> = Map::new("standings");
+pub const FEE_CAPS: Map<&str, FeeCaps> = Map::new("fee_caps");
+pub const REFERRERS: Map<Addr, Addr> = Map::new("referrers");
+pub const HOUSE: Map<&str, House> = Map::new("house");
+pub const SESSION_KEYS: Map<Addr, Session> = Map::new("session_keys");
+pub const MOVE_KEYS: Map<Addr, Binary> = Map::new("move_keys");
+pub const HOUSE_SHARES: Map<(&str, Addr), Uint128> = Map::new("house_shares");
+pub const REFERRAL_STATS: Map<Addr, ReferralStats> = Map::new("referral_stats");
 
 pub fn next_id(store: &mut dyn Storage) -> StdResult<u64> {
     let id: u64 = GAMES_COUNT.may_load(store)?.unwrap_or_default() + 1;
     GAMES_COUNT.save(store, &id)?;
     Ok(id)
 }
+
+pub fn next_series_id(store: &mut dyn Storage) -> StdResult<u64> {
+    let id: u64 = SERIES_COUNT.may_load(store)?.unwrap_or_default() + 1;
+    SERIES_COUNT.save(store, &id)?;
+    Ok(id)
+}
+
+pub fn next_tournament_id(store: &mut dyn Storage) -> StdResult<u64> {
+    let id: u64 = TOURNAMENTS_COUNT.may_load(store)?.unwrap_or_default() + 1;
+    TOURNAMENTS_COUNT.save(store, &id)?;
+    Ok(id)
+}
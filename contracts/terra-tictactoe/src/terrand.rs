@@ -9,8 +9,15 @@ pub struct LatestRandomResponse {
     pub worker: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GetRandomResponse {
+    pub randomness: Binary,
+    pub worker: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
+    GetRandomness { round: u64 },
     LatestDrand {},
 }
\ No newline at end of file
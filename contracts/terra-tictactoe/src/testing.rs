@@ -0,0 +1,59 @@
+//! Fixtures shared by the unit tests of the contract modules. Tests override the fields they
+//! care about with struct update syntax, so a new field only needs its default here.
+use cosmwasm_std::{coin, Addr, Binary, Timestamp};
+
+use crate::state::{BoardRules, Config, Game, GameState, RuleSet};
+
+pub fn mock_config() -> Config {
+    Config {
+        locked: false,
+        fee_bps: 200,
+        max_fee_bps: 1_000,
+        fee_tiers: vec![],
+        threshold: 4,
+        dimension: 6,
+        min_bet: coin(10, "uust"),
+        terrand_address: Addr::unchecked("terrand"),
+        referral_percentage: 0,
+        jackpot_bps: 0,
+        jackpot_odds: 0,
+        bets_close_after: 4,
+        house_exposure_bps: 0,
+        house_max_stake_bps: 0,
+        house_edge_bps: 0,
+    }
+}
+
+/// Game in progress on an empty 6x6 board between "host" and "opponent", "host" moves next.
+pub fn mock_game() -> Game {
+    Game {
+        game_id: 1,
+        host: Addr::unchecked("host"),
+        bet: coin(196, "uust"),
+        state: GameState::InProgress,
+        grid: vec![vec![0; 6]; 6],
+        opponent: Addr::unchecked("opponent"),
+        next_player: Addr::unchecked("host"),
+        updated_at: Timestamp::from_seconds(1_000),
+        winner: Addr::unchecked(""),
+        move_count: 0,
+        pending_offer: None,
+        auto_payout: false,
+        seed: Binary::default(),
+        rules: BoardRules {
+            dimension: 6,
+            threshold: 4,
+        },
+        tournament_id: None,
+        season_id: None,
+        series_id: None,
+        rule_set: RuleSet::Classic,
+        ultimate: None,
+        players: vec![Addr::unchecked("host"), Addr::unchecked("opponent")],
+        seats: 2,
+        teams: vec![],
+        vs_house: false,
+        resigned: vec![],
+        takebacks: 0,
+    }
+}
@@ -0,0 +1,213 @@
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+
+use crate::fees::TOTAL_WEIGHT;
+use crate::state::{Tournament, TournamentState};
+use crate::utils::generate_random_u8;
+use crate::ContractError;
+
+/// Largest bracket the Terrand randomness of a single round can shuffle.
+pub const MAX_BRACKET_SIZE: u32 = 16;
+
+/// Number of rounds played until a champion is left.
+pub fn rounds(bracket_size: u32) -> u32 {
+    bracket_size.trailing_zeros()
+}
+
+/// Shuffles the registered `players` into bracket order.
+pub fn seed_bracket(players: &[Addr], randomness: &Binary) -> Vec<Addr> {
+    let mut bracket = players.to_vec();
+    for i in (1..bracket.len()).rev() {
+        let j = generate_random_u8(randomness, i, (i + 1) as u8);
+        bracket.swap(i, usize::from(j));
+    }
+    bracket
+}
+
+/// Pairs up neighbours of `bracket` for the games of a round.
+pub fn pairings(bracket: &[Addr]) -> Vec<(Addr, Addr)> {
+    bracket
+        .chunks(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect()
+}
+
+/// Records `winner` of the current round game `game_id`. Returns true once every game of
+/// the round has a winner.
+pub fn record_winner(
+    tournament: &mut Tournament,
+    game_id: u64,
+    winner: &Addr,
+) -> Result<bool, ContractError> {
+    let pos = tournament
+        .games
+        .iter()
+        .position(|id| *id == game_id)
+        .ok_or(ContractError::GameNotFound {})?;
+    tournament.winners[pos] = Some(winner.clone());
+
+    Ok(tournament.winners.iter().all(Option::is_some))
+}
+
+/// Moves the winners of the finished round on and knocks the losers out. Returns the
+/// pairings of the next round, none once the final has been played.
+pub fn close_round(tournament: &mut Tournament) -> Vec<(Addr, Addr)> {
+    let winners: Vec<Addr> = tournament.winners.drain(..).flatten().collect();
+    let losers = tournament
+        .bracket
+        .iter()
+        .filter(|player| !winners.contains(player))
+        .cloned()
+        .collect();
+    tournament.eliminated.push(losers);
+    tournament.bracket = winners;
+    tournament.games.clear();
+
+    if tournament.bracket.len() == 1 {
+        tournament.state = TournamentState::Completed;
+        vec![]
+    } else {
+        tournament.round += 1;
+        pairings(&tournament.bracket)
+    }
+}
+
+/// Splits the prize pool of a completed tournament by how far each player got. Rounding
+/// dust goes to the champion.
+pub fn prize_payouts(tournament: &Tournament) -> Vec<(Addr, Coin)> {
+    let pool = tournament.entry_fee.amount * Uint128::from(tournament.bracket_size);
+    let champion = &tournament.bracket[0];
+    let mut payouts: Vec<(Addr, Uint128)> = vec![];
+    let mut paid = Uint128::zero();
+
+    for (place, bps) in tournament.prize_distribution.iter().enumerate() {
+        let players = match place {
+            0 => vec![champion.clone()],
+            _ => match tournament.eliminated.iter().rev().nth(place - 1) {
+                Some(players) => players.clone(),
+                None => continue,
+            },
+        };
+        let share = pool.multiply_ratio(u128::from(*bps), u128::from(TOTAL_WEIGHT));
+        let amount = share.multiply_ratio(1u128, players.len() as u128);
+        for player in players {
+            paid += amount;
+            payouts.push((player, amount));
+        }
+    }
+    payouts[0].1 += pool - paid;
+
+    payouts
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(player, amount)| {
+            (
+                player,
+                Coin {
+                    denom: tournament.entry_fee.denom.clone(),
+                    amount,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::BoardRules;
+    use cosmwasm_std::coin;
+    use std::convert::TryInto;
+
+    fn players(count: usize) -> Vec<Addr> {
+        (0..count)
+            .map(|i| Addr::unchecked(format!("player{}", i)))
+            .collect()
+    }
+
+    fn mock_tournament(prize_distribution: Vec<u16>) -> Tournament {
+        let players = players(4);
+        Tournament {
+            tournament_id: 1,
+            creator: Addr::unchecked("creator"),
+            entry_fee: coin(1_000, "uust"),
+            bracket_size: 4,
+            rules: BoardRules {
+                dimension: 6,
+                threshold: 4,
+            },
+            prize_distribution,
+            state: TournamentState::InProgress,
+            players: players.clone(),
+            round: 0,
+            bracket: players,
+            games: vec![1, 2],
+            winners: vec![None, None],
+            eliminated: vec![],
+        }
+    }
+
+    #[test]
+    fn seeding_keeps_every_player() {
+        let players = players(16);
+        let randomness = Binary::from(vec![7u8; 32]);
+        let mut bracket = seed_bracket(&players, &randomness);
+        assert_ne!(players, bracket);
+        bracket.sort();
+        let mut sorted = players;
+        sorted.sort();
+        assert_eq!(sorted, bracket);
+    }
+
+    #[test]
+    fn winners_advance_until_a_champion_is_left() {
+        let mut tournament = mock_tournament(vec![10_000]);
+        let [p0, p1, p2, p3]: [Addr; 4] = players(4).try_into().unwrap();
+        assert_eq!(2, rounds(tournament.bracket_size));
+        assert_eq!(
+            vec![(p0.clone(), p1.clone()), (p2.clone(), p3.clone())],
+            pairings(&tournament.bracket)
+        );
+
+        assert!(!record_winner(&mut tournament, 2, &p3).unwrap());
+        assert!(record_winner(&mut tournament, 1, &p0).unwrap());
+        match record_winner(&mut tournament, 3, &p0) {
+            Err(ContractError::GameNotFound {}) => {}
+            res => panic!("Must return GameNotFound error, got {:?}", res),
+        }
+
+        assert_eq!(vec![(p0.clone(), p3.clone())], close_round(&mut tournament));
+        assert_eq!(1, tournament.round);
+        assert_eq!(vec![vec![p1.clone(), p2.clone()]], tournament.eliminated);
+
+        tournament.games = vec![3];
+        tournament.winners = vec![None];
+        assert!(record_winner(&mut tournament, 3, &p3).unwrap());
+        assert!(close_round(&mut tournament).is_empty());
+        assert_eq!(TournamentState::Completed, tournament.state);
+        assert_eq!(vec![p3], tournament.bracket);
+        assert_eq!(vec![vec![p1, p2], vec![p0]], tournament.eliminated);
+    }
+
+    #[test]
+    fn prizes_follow_the_distribution() {
+        let [p0, p1, p2, p3]: [Addr; 4] = players(4).try_into().unwrap();
+        let mut tournament = mock_tournament(vec![6_001, 2_500, 1_499]);
+        tournament.state = TournamentState::Completed;
+        tournament.bracket = vec![p3.clone()];
+        tournament.eliminated = vec![vec![p1.clone(), p2.clone()], vec![p0.clone()]];
+
+        // Rounding down the 4_000 pool leaves two units of dust for the champion
+        assert_eq!(
+            vec![
+                (p3.clone(), coin(2_402, "uust")),
+                (p0, coin(1_000, "uust")),
+                (p1, coin(299, "uust")),
+                (p2, coin(299, "uust")),
+            ],
+            prize_payouts(&tournament)
+        );
+
+        tournament.prize_distribution = vec![10_000];
+        assert_eq!(vec![(p3, coin(4_000, "uust"))], prize_payouts(&tournament));
+    }
+}
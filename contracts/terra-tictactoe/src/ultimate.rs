@@ -0,0 +1,164 @@
+use crate::state::UltimateBoard;
+use crate::ContractError;
+
+/// Side of a sub-board, and of the grid of sub-boards.
+const SIDE: usize = 3;
+
+/// Mark of a full sub-board nobody won.
+pub const DRAWN: i8 = -1;
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+/// How a move ended a game of ultimate tic-tac-toe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoardResult {
+    Won,
+    Drawn,
+}
+
+pub fn new_board() -> UltimateBoard {
+    UltimateBoard {
+        cells: vec![0; SIDE.pow(4)],
+        boards: vec![0; SIDE * SIDE],
+        forced: None,
+    }
+}
+
+/// Sub-board and cell of that sub-board at `(x, y)` of the 9x9 grid, both numbered row by
+/// row.
+pub fn locate(x: u8, y: u8) -> Option<(usize, usize)> {
+    let (x, y) = (usize::from(x), usize::from(y));
+    if x >= SIDE * SIDE || y >= SIDE * SIDE {
+        return None;
+    }
+    Some(((x / SIDE) * SIDE + y / SIDE, (x % SIDE) * SIDE + y % SIDE))
+}
+
+fn has_line(cells: &[i8], mark: i8) -> bool {
+    LINES
+        .iter()
+        .any(|line| line.iter().all(|i| cells[*i] == mark))
+}
+
+/// Puts `mark` on `(x, y)` of `board`. The cell played sends the opponent to the sub-board
+/// in the same position, they can play anywhere once that sub-board is won or full.
+/// Returns how the move ended the game, if it did.
+pub fn play(
+    board: &mut UltimateBoard,
+    x: u8,
+    y: u8,
+    mark: i8,
+) -> Result<Option<BoardResult>, ContractError> {
+    let (sub, cell) = locate(x, y).ok_or(ContractError::MoveNotAllow {})?;
+    if matches!(board.forced, Some(forced) if usize::from(forced) != sub) {
+        return Err(ContractError::WrongSubBoard {});
+    }
+    let pos = sub * SIDE * SIDE + cell;
+    if board.boards[sub] != 0 || board.cells[pos] != 0 {
+        return Err(ContractError::MoveNotAllow {});
+    }
+    board.cells[pos] = mark;
+
+    let sub_cells = &board.cells[sub * SIDE * SIDE..(sub + 1) * SIDE * SIDE];
+    if has_line(sub_cells, mark) {
+        board.boards[sub] = mark;
+    } else if sub_cells.iter().all(|c| *c != 0) {
+        board.boards[sub] = DRAWN;
+    }
+    board.forced = match board.boards[cell] {
+        0 => Some(cell as u8),
+        _ => None,
+    };
+
+    if has_line(&board.boards, mark) {
+        Ok(Some(BoardResult::Won))
+    } else if board.boards.iter().all(|b| *b != 0) {
+        Ok(Some(BoardResult::Drawn))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_map_to_sub_boards() {
+        assert_eq!(Some((0, 0)), locate(0, 0));
+        assert_eq!(Some((4, 4)), locate(4, 4));
+        assert_eq!(Some((2, 3)), locate(1, 6));
+        assert_eq!(Some((7, 5)), locate(7, 5));
+        assert_eq!(Some((8, 8)), locate(8, 8));
+        assert_eq!(None, locate(9, 0));
+    }
+
+    #[test]
+    fn cell_played_forces_the_next_sub_board() {
+        let mut board = new_board();
+        assert_eq!(None, play(&mut board, 4, 4, 1).unwrap());
+        assert_eq!(Some(4), board.forced);
+        match play(&mut board, 0, 0, 100) {
+            Err(ContractError::WrongSubBoard {}) => {}
+            res => panic!("Must return WrongSubBoard error, got {:?}", res),
+        }
+        match play(&mut board, 4, 4, 100) {
+            Err(ContractError::MoveNotAllow {}) => {}
+            res => panic!("Must return MoveNotAllow error, got {:?}", res),
+        }
+        assert_eq!(None, play(&mut board, 3, 5, 100).unwrap());
+        assert_eq!(Some(2), board.forced);
+    }
+
+    #[test]
+    fn closed_sub_board_frees_the_next_move() {
+        let mut board = new_board();
+        board.cells[0] = 1;
+        board.cells[1] = 1;
+        board.forced = Some(0);
+        // Completing the top row of sub-board 0 in its own cell 2 wins it
+        assert_eq!(None, play(&mut board, 0, 2, 1).unwrap());
+        assert_eq!(1, board.boards[0]);
+        assert_eq!(Some(2), board.forced);
+
+        // Sent to the won sub-board 0, the opponent can play anywhere
+        assert_eq!(None, play(&mut board, 0, 6, 100).unwrap());
+        assert_eq!(None, board.forced);
+        assert_eq!(None, play(&mut board, 8, 8, 1).unwrap());
+        assert_eq!(Some(8), board.forced);
+        match play(&mut board, 1, 1, 100) {
+            Err(ContractError::WrongSubBoard {}) => {}
+            res => panic!("Must return WrongSubBoard error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn line_of_sub_boards_wins() {
+        let mut board = new_board();
+        board.boards[0] = 1;
+        board.boards[1] = 1;
+        board.cells[18] = 1;
+        board.cells[22] = 1;
+        assert_eq!(Some(BoardResult::Won), play(&mut board, 2, 8, 1).unwrap());
+        assert_eq!(1, board.boards[2]);
+    }
+
+    #[test]
+    fn closing_every_sub_board_without_a_line_draws() {
+        let mut board = new_board();
+        board.boards = vec![1, 100, 1, 1, 100, 100, 100, 1, 0];
+        board.cells[72..81].copy_from_slice(&[1, 100, 1, 1, 100, 100, 100, 1, 0]);
+        board.forced = Some(8);
+        assert_eq!(Some(BoardResult::Drawn), play(&mut board, 8, 8, 1).unwrap());
+        assert_eq!(DRAWN, board.boards[8]);
+    }
+}
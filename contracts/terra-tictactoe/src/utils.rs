@@ -1,11 +1,35 @@
-use cosmwasm_std::{to_binary, Binary, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
+use cosmwasm_std::{to_binary, Binary, Coin, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
 
-use crate::terrand::{LatestRandomResponse, QueryMsg as TerrandQueryMsg};
+use crate::terrand::{GetRandomResponse, LatestRandomResponse, QueryMsg as TerrandQueryMsg};
 
 pub fn get_randomness(querier: QuerierWrapper, terrand_oracle_address: String) -> StdResult<Binary> {
-    let response: LatestRandomResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+    Ok(get_latest_drand(querier, terrand_oracle_address)?.randomness)
+}
+
+/// Number of the latest round published by Terrand.
+pub fn get_latest_round(querier: QuerierWrapper, terrand_oracle_address: String) -> StdResult<u64> {
+    Ok(get_latest_drand(querier, terrand_oracle_address)?.round)
+}
+
+fn get_latest_drand(
+    querier: QuerierWrapper,
+    terrand_oracle_address: String,
+) -> StdResult<LatestRandomResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: terrand_oracle_address,
         msg: to_binary(&TerrandQueryMsg::LatestDrand {})?,
+    }))
+}
+
+/// Randomness of the Terrand `round`, which has to be out already.
+pub fn get_round_randomness(
+    querier: QuerierWrapper,
+    terrand_oracle_address: String,
+    round: u64,
+) -> StdResult<Binary> {
+    let response: GetRandomResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: terrand_oracle_address,
+        msg: to_binary(&TerrandQueryMsg::GetRandomness { round })?,
     }))?;
 
     Ok(response.randomness)
@@ -21,3 +45,11 @@ pub fn generate_random_u8(randomness: &Binary, round: usize, max: u8) -> u8 {
 
     random_ranged_number
 }
+
+/// Adds `coin` to the matching denom of `coins`, keeping `coins` sorted by denom.
+pub fn add_coin(coins: &mut Vec<Coin>, coin: &Coin) {
+    match coins.binary_search_by(|c| c.denom.cmp(&coin.denom)) {
+        Ok(pos) => coins[pos].amount += coin.amount,
+        Err(pos) => coins.insert(pos, coin.clone()),
+    }
+}
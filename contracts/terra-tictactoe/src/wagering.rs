@@ -0,0 +1,217 @@
+use cosmwasm_std::Uint128;
+
+use crate::state::{BetMarket, BetOutcome, BetPosition, Game, GameState};
+
+/// How the side bets on a game are settled once the game is over.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolution {
+    /// Bets on the outcome share the whole pool, less the house fee.
+    Outcome(BetOutcome),
+    /// Every bet is refunded in full.
+    Refund,
+}
+
+/// Side bets are taken on two player games until `bets_close_after` moves have been played.
+pub fn is_betting_open(game: &Game, bets_close_after: u32) -> bool {
+    if game.seats != 2 {
+        return false;
+    }
+    match game.state {
+        GameState::New => true,
+        GameState::InProgress => game.move_count < bets_close_after,
+        _ => false,
+    }
+}
+
+/// Settles the side bets on `game`, none while the game is still running. A game nobody
+/// bet on the outcome of is refunded like a cancelled one.
+pub fn resolve(game: &Game, market: &BetMarket) -> Option<Resolution> {
+    let outcome = match game.state {
+        GameState::New | GameState::InProgress => return None,
+        GameState::Cancelled => return Some(Resolution::Refund),
+        GameState::Drawn => BetOutcome::Draw,
+        GameState::Completed | GameState::PriceWithdrawn if game.winner == game.host => {
+            BetOutcome::Host
+        }
+        GameState::Completed | GameState::PriceWithdrawn => BetOutcome::Opponent,
+    };
+    if stake_on(&market.pools, outcome).is_zero() {
+        Some(Resolution::Refund)
+    } else {
+        Some(Resolution::Outcome(outcome))
+    }
+}
+
+/// Amount staked on `outcome`.
+pub fn stake_on(position: &BetPosition, outcome: BetOutcome) -> Uint128 {
+    match outcome {
+        BetOutcome::Host => position.host,
+        BetOutcome::Opponent => position.opponent,
+        BetOutcome::Draw => position.draw,
+    }
+}
+
+/// Adds `amount` on `outcome` to `position`.
+pub fn add_stake(position: &mut BetPosition, outcome: BetOutcome, amount: Uint128) {
+    match outcome {
+        BetOutcome::Host => position.host += amount,
+        BetOutcome::Opponent => position.opponent += amount,
+        BetOutcome::Draw => position.draw += amount,
+    }
+}
+
+pub fn total_stake(position: &BetPosition) -> Uint128 {
+    position.host + position.opponent + position.draw
+}
+
+/// Parimutuel payout of `position`: its share of the winning pool in the whole pool less
+/// the `fee` of the house. The last winner to claim gets whatever rounding left of the pot.
+pub fn payout(
+    market: &BetMarket,
+    position: &BetPosition,
+    resolution: Resolution,
+    fee: Uint128,
+) -> Uint128 {
+    match resolution {
+        Resolution::Refund => total_stake(position),
+        Resolution::Outcome(outcome) => {
+            let pot = total_stake(&market.pools) - fee;
+            let stake = stake_on(position, outcome);
+            if market.claimed + stake == stake_on(&market.pools, outcome) {
+                pot - market.paid
+            } else {
+                pot.multiply_ratio(stake, stake_on(&market.pools, outcome))
+            }
+        }
+    }
+}
+
+/// Counts the claim of `position`, paid `amount`, against the winning pool of `market`.
+pub fn record_claim(
+    market: &mut BetMarket,
+    position: &BetPosition,
+    resolution: Resolution,
+    amount: Uint128,
+) {
+    if let Resolution::Outcome(outcome) = resolution {
+        market.claimed += stake_on(position, outcome);
+        market.paid += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use cosmwasm_std::Addr;
+
+    fn mock_game(state: GameState, winner: &str, move_count: u32) -> Game {
+        Game {
+            state,
+            winner: Addr::unchecked(winner),
+            move_count,
+            ..testing::mock_game()
+        }
+    }
+
+    fn position(host: u128, opponent: u128, draw: u128) -> BetPosition {
+        BetPosition {
+            host: Uint128::new(host),
+            opponent: Uint128::new(opponent),
+            draw: Uint128::new(draw),
+        }
+    }
+
+    fn mock_market(pools: BetPosition) -> BetMarket {
+        BetMarket {
+            game_id: 1,
+            denom: "uust".to_string(),
+            pools,
+            fee: None,
+            claimed: Uint128::zero(),
+            paid: Uint128::zero(),
+        }
+    }
+
+    #[test]
+    fn betting_closes_after_the_cutoff() {
+        assert!(is_betting_open(&mock_game(GameState::New, "", 0), 0));
+        assert!(is_betting_open(&mock_game(GameState::InProgress, "", 3), 4));
+        assert!(!is_betting_open(
+            &mock_game(GameState::InProgress, "", 4),
+            4
+        ));
+        assert!(!is_betting_open(&mock_game(GameState::Drawn, "", 1), 4));
+    }
+
+    #[test]
+    fn bets_resolve_with_the_game() {
+        let market = mock_market(position(100, 50, 0));
+        assert_eq!(
+            None,
+            resolve(&mock_game(GameState::InProgress, "", 3), &market)
+        );
+        assert_eq!(
+            Some(Resolution::Outcome(BetOutcome::Host)),
+            resolve(&mock_game(GameState::Completed, "host", 7), &market)
+        );
+        assert_eq!(
+            Some(Resolution::Outcome(BetOutcome::Opponent)),
+            resolve(
+                &mock_game(GameState::PriceWithdrawn, "opponent", 8),
+                &market
+            )
+        );
+        assert_eq!(
+            Some(Resolution::Refund),
+            resolve(&mock_game(GameState::Cancelled, "", 0), &market)
+        );
+        // Nobody bet on a draw
+        assert_eq!(
+            Some(Resolution::Refund),
+            resolve(&mock_game(GameState::Drawn, "", 5), &market)
+        );
+    }
+
+    #[test]
+    fn winners_share_the_pool() {
+        let market = mock_market(position(300, 100, 100));
+        let resolution = Resolution::Outcome(BetOutcome::Host);
+        let fee = Uint128::new(50);
+        assert_eq!(
+            Uint128::new(300),
+            payout(&market, &position(200, 0, 0), resolution, fee)
+        );
+        assert_eq!(
+            Uint128::new(150),
+            payout(&market, &position(100, 100, 0), resolution, fee)
+        );
+        assert_eq!(
+            Uint128::zero(),
+            payout(&market, &position(0, 0, 100), resolution, fee)
+        );
+        assert_eq!(
+            Uint128::new(200),
+            payout(&market, &position(100, 100, 0), Resolution::Refund, fee)
+        );
+    }
+
+    #[test]
+    fn last_winner_takes_the_rounding_dust() {
+        let mut market = mock_market(position(3, 7, 0));
+        let resolution = Resolution::Outcome(BetOutcome::Host);
+        let fee = Uint128::zero();
+        let mut paid = vec![];
+        for _ in 0..3 {
+            let amount = payout(&market, &position(1, 0, 0), resolution, fee);
+            record_claim(&mut market, &position(1, 0, 0), resolution, amount);
+            paid.push(amount.u128());
+        }
+        assert_eq!(vec![3, 3, 4], paid);
+        // Losers claiming after the last winner get nothing
+        assert_eq!(
+            Uint128::zero(),
+            payout(&market, &position(0, 7, 0), resolution, fee)
+        );
+    }
+}